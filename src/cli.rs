@@ -0,0 +1,341 @@
+// Подкоманды без окна: анализ сохраненных тестов, симуляция и проверка ботом
+use serde::Serialize;
+use std::path::Path;
+
+use crate::bot::{drive_synthetic_aimer, run_bot_validation, BotConfig, SyntheticAimer};
+use crate::config::config::load_config;
+use crate::config::scenarios::load_scenarios;
+use crate::detector::DetectorRegistry;
+use crate::export::{
+    load_run_export, load_run_with_config, save_run_export, unix_timestamp, RunExport, RunSummary,
+};
+use crate::ghost::ghost_from_run;
+use crate::headless::{build_headless_app, run_headless_test};
+use crate::state::*;
+use crate::target::{analyze_results, trace_jumps, update_target};
+use bevy::prelude::*;
+
+const USAGE: &str = "Usage:
+  mVRT                                   start the game
+  mVRT analyze [--settings recorded|current] <run.json|run.mvrt>...
+                                         re-analyse exported runs, print JSON
+  mVRT replay <run.json|run.mvrt>        open the game window with the run replay
+  mVRT ghost <run.json|run.mvrt>         open the game window and race the run's ghost
+  mVRT simulate [--scenario name] [--seed n] [--out dir] [bot options]
+                                         run the target model without a window, print JSON
+  mVRT validate [--scenario name] [--seed n] [bot options]
+                                         check that the detector recovers the bot delay
+
+Bot options: --delay ms, --gain g, --noise deg/s, --overshoot fraction";
+
+// Коды выхода
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+// None - подкоманды нет, запускаем игру
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let result = match command.as_str() {
+        "analyze" => analyze_command(rest),
+        "simulate" => simulate_command(rest),
+        "validate" => validate_command(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(EXIT_OK)
+        }
+        _ => Err(format!("Unknown command '{}'\n\n{}", command, USAGE)),
+    };
+
+    Some(result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        EXIT_ERROR
+    }))
+}
+
+// `mVRT replay <run.json>` - не подкоманда без окна: игра стартует сразу с просмотром
+pub fn replay_from_args(args: &[String]) -> Result<Option<ReplayViewer>, String> {
+    match args.split_first() {
+        Some((command, rest)) if command == "replay" => {
+            let [file] = rest else {
+                return Err(format!("Expected one run file\n\n{}", USAGE));
+            };
+            let export =
+                load_run_export(Path::new(file)).map_err(|e| format!("{}: {}", file, e))?;
+            Ok(Some(ReplayViewer::open(
+                format!("{}, seed {}", export.scenario.name, export.seed),
+                export.data,
+                export.direction_changes,
+                export.reactions,
+                trace_jumps(&export.target_switches, &export.kills),
+            )))
+        }
+        _ => Ok(None),
+    }
+}
+
+// `mVRT ghost <run.json>` - тоже с окном: следующие тесты идут против записанного прицела
+pub fn ghost_from_args(args: &[String]) -> Result<Option<GhostRace>, String> {
+    match args.split_first() {
+        Some((command, rest)) if command == "ghost" => {
+            let [file] = rest else {
+                return Err(format!("Expected one run file\n\n{}", USAGE));
+            };
+            let export =
+                load_run_export(Path::new(file)).map_err(|e| format!("{}: {}", file, e))?;
+            ghost_from_run(
+                format!("{}, seed {}", export.scenario.name, export.seed),
+                &export,
+            )
+            .map(Some)
+            .map_err(|e| format!("{}: {}", file, e))
+        }
+        _ => Ok(None),
+    }
+}
+
+// Разобранные аргументы: позиционные и пары "--флаг значение"
+struct CliArgs {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl CliArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg.starts_with("--") {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))?;
+                options.push((arg.clone(), value.clone()));
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    // Ошибка, если есть опция не из списка
+    fn check(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .options
+            .iter()
+            .find(|(f, _)| !known.contains(&f.as_str()))
+        {
+            Some((flag, _)) => Err(format!("Unknown option {}\n\n{}", flag, USAGE)),
+            None => Ok(()),
+        }
+    }
+
+    fn get<T: std::str::FromStr>(&self, flag: &str) -> Result<Option<T>, String> {
+        match self.options.iter().rev().find(|(f, _)| f == flag) {
+            Some((_, value)) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid value for {}: {}", flag, value)),
+            None => Ok(None),
+        }
+    }
+
+    // Параметры бота; None, если ни одна опция бота не указана
+    fn bot_config(&self) -> Result<Option<BotConfig>, String> {
+        let delay: Option<f32> = self.get("--delay")?;
+        let gain: Option<f32> = self.get("--gain")?;
+        let noise: Option<f32> = self.get("--noise")?;
+        let overshoot: Option<f32> = self.get("--overshoot")?;
+        if delay.is_none() && gain.is_none() && noise.is_none() && overshoot.is_none() {
+            return Ok(None);
+        }
+
+        let mut bot = BotConfig::default();
+        if let Some(delay) = delay {
+            bot.reaction_delay = delay / 1000.0;
+        }
+        bot.gain = gain.unwrap_or(bot.gain);
+        bot.noise = noise.unwrap_or(bot.noise);
+        bot.overshoot = overshoot.unwrap_or(bot.overshoot);
+        Ok(Some(bot))
+    }
+}
+
+const BOT_OPTIONS: [&str; 4] = ["--delay", "--gain", "--noise", "--overshoot"];
+
+// Настройки из assets/config.json, без записи файла по умолчанию
+fn load_settings() -> Settings {
+    load_config(&ConfigState::default().config_path)
+        .map(Settings::from)
+        .unwrap_or_default()
+}
+
+// Сценарий и seed из аргументов, иначе из настроек
+fn scenario_and_seed(args: &CliArgs, settings: &Settings) -> Result<(Scenario, u64), String> {
+    let library = ScenarioLibrary {
+        scenarios: load_scenarios(SCENARIOS_DIR),
+    };
+    let name = args
+        .get::<String>("--scenario")?
+        .unwrap_or_else(|| settings.scenario.clone());
+    if !library.scenarios.iter().any(|s| s.name == name) {
+        return Err(format!("Unknown scenario '{}'", name));
+    }
+    let seed = match args.get("--seed")? {
+        Some(seed) => seed,
+        None => settings.next_run_seed(),
+    };
+    Ok((library.get(&name), seed))
+}
+
+#[derive(Serialize)]
+struct AnalysisReport {
+    file: String,
+    scenario: String,
+    seed: u64,
+    // Метрики, сохраненные при экспорте
+    previous: RunSummary,
+    // Чьи настройки детектора: "recorded" - из записи .mvrt, "current" - из assets/config.json
+    detector_settings: &'static str,
+    // Метрики текущего детектора
+    summary: RunSummary,
+    detectors: Vec<DetectorReport>,
+}
+
+// `mVRT analyze run.json...` - один файл печатается объектом, несколько - массивом
+fn analyze_command(args: &[String]) -> Result<i32, String> {
+    let args = CliArgs::parse(args)?;
+    args.check(&["--settings"])?;
+    if args.positional.is_empty() {
+        return Err(format!("No run files given\n\n{}", USAGE));
+    }
+    // Запись .mvrt хранит настройки детектора, с которыми тест был посчитан;
+    // current - пересчитать с порогами из assets/config.json
+    let use_recorded = match args.get::<String>("--settings")?.as_deref() {
+        None | Some("recorded") => true,
+        Some("current") => false,
+        Some(other) => return Err(format!("Invalid value for --settings: {}", other)),
+    };
+
+    let current = DetectorRegistry::from_settings(&load_settings());
+    let mut reports = Vec::new();
+    for file in &args.positional {
+        let (export, config) =
+            load_run_with_config(Path::new(file)).map_err(|e| format!("{}: {}", file, e))?;
+        let report = match config.filter(|_| use_recorded) {
+            Some(config) => {
+                let recorded = DetectorRegistry::from_settings(&Settings::from(config));
+                analyze_export(file, &export, &recorded, "recorded")
+            }
+            None => analyze_export(file, &export, &current, "current"),
+        };
+        reports.push(report);
+    }
+
+    let json = if reports.len() == 1 {
+        serde_json::to_string_pretty(&reports[0])
+    } else {
+        serde_json::to_string_pretty(&reports)
+    }
+    .map_err(|e| e.to_string())?;
+    println!("{}", json);
+    Ok(EXIT_OK)
+}
+
+fn analyze_export(
+    file: &str,
+    export: &RunExport,
+    detectors: &DetectorRegistry,
+    detector_settings: &'static str,
+) -> AnalysisReport {
+    let mut test = export.to_test();
+    analyze_results(&mut test, detectors);
+    AnalysisReport {
+        file: file.to_string(),
+        scenario: export.scenario.name.clone(),
+        seed: export.seed,
+        previous: export.summary.clone(),
+        detector_settings,
+        summary: RunSummary::from(&test),
+        detectors: test.detector_reports,
+    }
+}
+
+#[derive(Serialize)]
+struct SimulationReport {
+    scenario: String,
+    seed: u64,
+    ticks: usize,
+    bot: Option<BotConfig>,
+    summary: RunSummary,
+    detectors: Vec<DetectorReport>,
+    // Пути к сохраненной телеметрии (--out)
+    files: Vec<String>,
+}
+
+// `mVRT simulate` - прогон модели таргета; с опциями бота прицел ведет бот
+fn simulate_command(args: &[String]) -> Result<i32, String> {
+    let args = CliArgs::parse(args)?;
+    let mut known = vec!["--scenario", "--seed", "--out"];
+    known.extend(BOT_OPTIONS);
+    args.check(&known)?;
+    if let Some(extra) = args.positional.first() {
+        return Err(format!("Unexpected argument '{}'\n\n{}", extra, USAGE));
+    }
+
+    let settings = load_settings();
+    let (scenario, seed) = scenario_and_seed(&args, &settings)?;
+    let bot = args.bot_config()?;
+
+    let mut app = build_headless_app(settings, scenario, seed);
+    if let Some(bot) = &bot {
+        app.insert_resource(SyntheticAimer::new(bot.clone(), seed))
+            .add_systems(FixedUpdate, drive_synthetic_aimer.before(update_target));
+    }
+    if !run_headless_test(&mut app) {
+        return Err("Simulation did not finish".to_string());
+    }
+
+    let test = app.world().resource::<ReactionTest>();
+    let mut files = Vec::new();
+    if let Some(dir) = args.get::<String>("--out")? {
+        let export = RunExport::from_test(test, unix_timestamp());
+        let config = GameConfig::from(app.world().resource::<Settings>());
+        let paths = save_run_export(&export, &config, &dir).map_err(|e| e.to_string())?;
+        files = paths.iter().map(|p| p.display().to_string()).collect();
+    }
+
+    let report = SimulationReport {
+        scenario: test.scenario.name.clone(),
+        seed: test.seed,
+        ticks: test.data.len(),
+        bot,
+        summary: RunSummary::from(test),
+        detectors: test.detector_reports.clone(),
+        files,
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+    );
+    Ok(EXIT_OK)
+}
+
+// `mVRT validate` - 0, если детектор восстановил задержку бота, иначе 1
+fn validate_command(args: &[String]) -> Result<i32, String> {
+    let args = CliArgs::parse(args)?;
+    let mut known = vec!["--scenario", "--seed"];
+    known.extend(BOT_OPTIONS);
+    args.check(&known)?;
+
+    let settings = load_settings();
+    let (scenario, seed) = scenario_and_seed(&args, &settings)?;
+    let bot = args.bot_config()?.unwrap_or_default();
+
+    let report = run_bot_validation(settings, scenario, seed, bot)?;
+    println!("{}", report.to_text());
+    Ok(if report.passed { EXIT_OK } else { EXIT_FAILED })
+}
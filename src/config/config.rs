@@ -4,6 +4,7 @@ use std::fs;
 use std::path::Path;
 
 //use super::cfgsettings::{color_to_hex, hex_to_color, GameConfig};
+use super::scenarios::load_scenarios_system;
 use crate::state::*;
 
 pub struct ConfigPlugin;
@@ -11,8 +12,12 @@ pub struct ConfigPlugin;
 impl Plugin for ConfigPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ConfigState>()
+            .init_resource::<ScenarioLibrary>()
             .add_event::<SaveConfigEvent>()
-            .add_systems(OnEnter(AppState::Loading), load_config_and_init_settings)
+            .add_systems(
+                OnEnter(AppState::Loading),
+                (load_config_and_init_settings, load_scenarios_system),
+            )
             .add_systems(Update, (auto_save_config_system, save_config_event_system))
             .add_systems(OnExit(AppState::Settings), save_config_on_settings_exit);
     }
@@ -36,10 +41,22 @@ impl From<GameConfig> for Settings {
         settings.texture_file = config.texture_file;
         settings.font_file = config.font_file;
         settings.hit_sound_file = config.hit_sound_file;
+        settings.stimulus_sound_files = config.stimulus_sound_files;
+        settings.audio_output_latency = config.audio_output_latency;
         settings.key_restart = config.key_restart;
         settings.key_start = config.key_start;
         settings.key_settings = config.key_settings;
         settings.key_fullscreen = config.key_fullscreen;
+        settings.key_history = config.key_history;
+        settings.key_replay = config.key_replay;
+        settings.key_ghost = config.key_ghost;
+        settings.key_shoot = config.key_shoot;
+        settings.scenario = config.scenario;
+        settings.seed = config.seed;
+        settings.test_duration = config.test_duration;
+        settings.countdown = config.countdown;
+        settings.detector = config.detector;
+        settings.heuristic_detector = config.heuristic_detector;
 
         // Конвертируем HEX цвета в HSL компоненты
         if let Ok(crosshair_color) = hex_to_color(&config.crosshair_color) {
@@ -84,10 +101,22 @@ impl From<&Settings> for GameConfig {
             texture_file: settings.texture_file.clone(),
             font_file: settings.font_file.clone(),
             hit_sound_file: settings.hit_sound_file.clone(),
+            stimulus_sound_files: settings.stimulus_sound_files.clone(),
+            audio_output_latency: settings.audio_output_latency,
             key_restart: settings.key_restart,
             key_start: settings.key_start,
             key_settings: settings.key_settings,
             key_fullscreen: settings.key_fullscreen,
+            key_history: settings.key_history,
+            key_replay: settings.key_replay,
+            key_ghost: settings.key_ghost,
+            key_shoot: settings.key_shoot,
+            scenario: settings.scenario.clone(),
+            seed: settings.seed,
+            test_duration: settings.test_duration,
+            countdown: settings.countdown,
+            detector: settings.detector.clone(),
+            heuristic_detector: settings.heuristic_detector.clone(),
         }
     }
 }
@@ -123,7 +152,7 @@ pub fn save_config_event_system(
 }
 
 // Функция для загрузки конфига из файла
-fn load_config(path: &str) -> Result<GameConfig, Box<dyn std::error::Error>> {
+pub fn load_config(path: &str) -> Result<GameConfig, Box<dyn std::error::Error>> {
     if !Path::new(path).exists() {
         return Err("Config file does not exist".into());
     }
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod scenarios;
 pub mod settings;
 pub use config::ConfigPlugin;
 
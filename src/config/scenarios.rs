@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+use log::{error, info, warn};
+use std::fs;
+use std::path::Path;
+
+use crate::state::*;
+
+// Система для загрузки сценариев из assets/scenarios при запуске
+pub fn load_scenarios_system(mut library: ResMut<ScenarioLibrary>) {
+    library.scenarios = load_scenarios(SCENARIOS_DIR);
+    info!(
+        "Loaded {} scenario(s) from {}",
+        library.scenarios.len(),
+        SCENARIOS_DIR
+    );
+}
+
+// Сканирует директорию и загружает все *.json сценарии, отсортированные по имени файла
+pub fn load_scenarios(dir: &str) -> Vec<Scenario> {
+    let mut paths: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to read scenarios directory {}: {}", dir, e);
+            Vec::new()
+        }
+    };
+    paths.sort();
+
+    let mut scenarios: Vec<Scenario> = Vec::new();
+    for path in paths {
+        match load_scenario(&path) {
+            Ok(scenario) => {
+                if scenarios.iter().any(|s| s.name == scenario.name) {
+                    warn!(
+                        "Duplicate scenario name '{}' in {}, skipping",
+                        scenario.name,
+                        path.display()
+                    );
+                    continue;
+                }
+                scenarios.push(scenario);
+            }
+            Err(e) => {
+                error!("Failed to load scenario {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    if scenarios.is_empty() {
+        // Создаем сценарий по умолчанию, чтобы было что редактировать
+        let default_scenario = Scenario::default();
+        let path = Path::new(dir).join("default.json");
+        if let Err(e) = save_scenario(&default_scenario, &path) {
+            error!("Failed to create default scenario file: {}", e);
+        } else {
+            info!("Default scenario created at {}", path.display());
+        }
+        scenarios.push(default_scenario);
+    }
+
+    scenarios
+}
+
+pub fn load_scenario(path: &Path) -> Result<Scenario, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path)?;
+    let scenario: Scenario = serde_json::from_str(&content)?;
+    validate_scenario(&scenario)?;
+    Ok(scenario)
+}
+
+fn save_scenario(scenario: &Scenario, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let scenario_json = serde_json::to_string_pretty(scenario)?;
+    fs::write(path, scenario_json)?;
+    Ok(())
+}
+
+fn validate_scenario(scenario: &Scenario) -> Result<(), String> {
+    if scenario.name.trim().is_empty() {
+        return Err("Scenario name must not be empty".to_string());
+    }
+    if scenario.target_size <= 0.0 || scenario.target_speed < 0.0 {
+        return Err("Target size must be positive and speed non-negative".to_string());
+    }
+    if scenario.test_duration <= 0.0 {
+        return Err("Test duration must be positive".to_string());
+    }
+    if scenario.warmup < 0.0 || scenario.warmup >= scenario.test_duration {
+        return Err("Warm-up must be non-negative and shorter than the test".to_string());
+    }
+    if scenario.target_bound_x <= 0.0
+        || scenario.target_bound_y <= 0.0
+        || scenario.target_bound_z_min <= 0.0
+        || scenario.target_bound_z_min > scenario.target_bound_z_max
+    {
+        return Err("Invalid target bounds".to_string());
+    }
+    if scenario.change_interval_min <= 0.0
+        || scenario.change_interval_min > scenario.change_interval_max
+    {
+        return Err("Invalid direction change interval".to_string());
+    }
+    if !(0.0..180.0).contains(&scenario.min_turn_angle) {
+        return Err("Minimum turn angle must be in [0, 180) degrees".to_string());
+    }
+    if scenario.depth_speed < 0.0 || scenario.distance_sweep_period < 0.0 {
+        return Err("depth_speed and distance_sweep_period must not be negative".to_string());
+    }
+    if scenario.target_health < 0.0
+        || (scenario.target_health > 0.0 && scenario.damage_per_second <= 0.0)
+    {
+        return Err("Invalid target_health or damage_per_second".to_string());
+    }
+    if scenario.target_count == 0 || scenario.target_count > MAX_TARGET_COUNT {
+        return Err(format!("Target count must be in 1..={}", MAX_TARGET_COUNT));
+    }
+    if scenario.target_count > 1
+        && (scenario.switch_interval_min <= 0.0
+            || scenario.switch_interval_min > scenario.switch_interval_max)
+    {
+        return Err("Invalid target switch interval".to_string());
+    }
+    match &scenario.motion {
+        MotionConfig::RandomTurn => {}
+        MotionConfig::Sinusoidal(m) => {
+            if m.period_x <= 0.0 || m.period_y <= 0.0 {
+                return Err("Sinusoidal periods must be positive".to_string());
+            }
+        }
+        MotionConfig::Adad(m) => {
+            if m.acceleration <= 0.0 || m.strafe_min <= 0.0 || m.strafe_min > m.strafe_max {
+                return Err("Invalid ADAD motion parameters".to_string());
+            }
+        }
+        MotionConfig::JerkLimited(m) => {
+            if m.max_acceleration <= 0.0
+                || m.max_jerk <= 0.0
+                || m.change_interval_min <= 0.0
+                || m.change_interval_min > m.change_interval_max
+            {
+                return Err("Invalid jerk-limited motion parameters".to_string());
+            }
+        }
+    }
+    if scenario.mode != TestMode::Tracking && scenario.target_count > 1 {
+        return Err("Flick and reaction scenarios use a single target".to_string());
+    }
+    if let Some(staircase) = &scenario.staircase {
+        if scenario.mode != TestMode::Tracking {
+            return Err("Staircase needs \"mode\": \"tracking\"".to_string());
+        }
+        if staircase.block_duration <= 0.0
+            || !(0.0..=1.0).contains(&staircase.on_target_threshold)
+            || staircase.step <= 0.0
+            || staircase.up == 0
+            || staircase.down == 0
+            || staircase.min_level <= 0.0
+            || staircase.min_level > 1.0
+            || staircase.max_level < 1.0
+        {
+            return Err("Invalid staircase parameters".to_string());
+        }
+        // Лестница меняет только то, что модель движения читает
+        match (&scenario.motion, staircase.parameter) {
+            (_, StaircaseParameter::Size) => {}
+            (MotionConfig::Sinusoidal(_), _) => {
+                return Err(
+                    "Sinusoidal motion ignores target speed and turn intervals: use a size staircase"
+                        .to_string(),
+                );
+            }
+            (MotionConfig::Adad(_), StaircaseParameter::ChangeInterval) => {
+                return Err(
+                    "ADAD motion has no turn intervals: use a speed or size staircase".to_string(),
+                );
+            }
+            _ => {}
+        }
+    }
+    if scenario.stimulus != StimulusKind::Visual && scenario.mode != TestMode::Reaction {
+        return Err("Auditory stimuli need \"mode\": \"reaction\"".to_string());
+    }
+    if scenario.mode.is_reaction()
+        && (scenario.reaction_trials == 0
+            || scenario.foreperiod_min <= 0.0
+            || scenario.foreperiod_min > scenario.foreperiod_max
+            || !(0.0..=1.0).contains(&scenario.go_probability))
+    {
+        return Err(
+            "Reaction scenarios need trials > 0, 0 < foreperiod_min <= foreperiod_max and go_probability in 0..1"
+                .to_string(),
+        );
+    }
+    if scenario.mode == TestMode::Flick
+        && (scenario.flick_trials == 0
+            || scenario.flick_min_angle < 0.0
+            || scenario.flick_min_angle > scenario.flick_max_angle
+            || scenario.flick_max_angle > 90.0)
+    {
+        return Err(
+            "Flick scenarios need trials > 0 and 0 <= min <= max <= 90 degrees".to_string(),
+        );
+    }
+    Ok(())
+}
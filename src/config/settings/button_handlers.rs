@@ -152,3 +152,33 @@ pub fn settings_button_system(
         }
     }
 }
+
+// Переключение сценария по клику
+pub fn handle_scenario_selector(
+    mut settings: ResMut<Settings>,
+    scenarios: Res<ScenarioLibrary>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<ScenarioSelector>)>,
+    mut name_query: Query<
+        &mut Text,
+        (With<ScenarioSelectorText>, Without<ScenarioDescriptionText>),
+    >,
+    mut description_query: Query<&mut Text, With<ScenarioDescriptionText>>,
+) {
+    for interaction in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(next) = scenarios.next_name(&settings.scenario) else {
+            continue;
+        };
+        settings.scenario = next;
+
+        let scenario = scenarios.get(&settings.scenario);
+        for mut text in name_query.iter_mut() {
+            text.0 = format!("< {} >", scenario.name);
+        }
+        for mut text in description_query.iter_mut() {
+            text.0 = scenario.description.clone();
+        }
+    }
+}
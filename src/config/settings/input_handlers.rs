@@ -480,3 +480,173 @@ pub fn clear_other_editing_states_system(
         }
     }
 }
+
+// Seed input box system
+pub fn seed_input_box_system(
+    mut settings: ResMut<Settings>,
+    mut focus: ResMut<InputFocus>,
+    mut seed_nodes: Query<(Entity, &Interaction, &Children, Option<&SeedEditing>), With<SeedInput>>,
+    mut seed_texts: Query<(&mut Text, &mut SeedBuffer)>,
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    for (entity, interaction, children, editing) in seed_nodes.iter_mut() {
+        if *interaction == Interaction::Pressed {
+            focus.focused = Some(InputField::Seed);
+            commands.entity(entity).insert(SeedEditing);
+            if let Some(&child) = children.first() {
+                if let Ok((_, mut buf)) = seed_texts.get_mut(child) {
+                    buf.0.clear();
+                }
+            }
+        }
+        let lost_focus = !matches!(focus.focused, Some(InputField::Seed));
+        if editing.is_some()
+            && (lost_focus
+                || (*interaction == Interaction::None && keys.just_pressed(KeyCode::Escape)))
+        {
+            commands.entity(entity).remove::<SeedEditing>();
+            if let Some(&child) = children.first() {
+                if let Ok((mut text, mut buf)) = seed_texts.get_mut(child) {
+                    buf.0 = settings.seed.to_string();
+                    text.0 = buf.0.clone();
+                }
+            }
+            if !lost_focus {
+                focus.focused = None;
+            }
+            continue;
+        }
+        if editing.is_some() {
+            if let Some(&child) = children.first() {
+                if let Ok((mut text, mut buf)) = seed_texts.get_mut(child) {
+                    process_numeric_input(&keys, &mut buf.0, false);
+                    // Seed - только целое число
+                    buf.0.retain(|c| c.is_ascii_digit());
+                    if let Ok(val) = buf.0.parse::<u64>() {
+                        settings.seed = val;
+                    } else if buf.0.is_empty() {
+                        settings.seed = 0;
+                    }
+                    text.0 = format!("{}|", buf.0);
+                }
+            }
+        }
+    }
+}
+
+// Test duration input box system
+pub fn test_duration_input_box_system(
+    mut settings: ResMut<Settings>,
+    mut focus: ResMut<InputFocus>,
+    mut duration_nodes: Query<
+        (
+            Entity,
+            &Interaction,
+            &Children,
+            Option<&TestDurationEditing>,
+        ),
+        With<TestDurationInput>,
+    >,
+    mut duration_texts: Query<(&mut Text, &mut TestDurationBuffer)>,
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    for (entity, interaction, children, editing) in duration_nodes.iter_mut() {
+        if *interaction == Interaction::Pressed {
+            focus.focused = Some(InputField::TestDuration);
+            commands.entity(entity).insert(TestDurationEditing);
+            if let Some(&child) = children.first() {
+                if let Ok((_, mut buf)) = duration_texts.get_mut(child) {
+                    buf.0.clear();
+                }
+            }
+        }
+        let lost_focus = !matches!(focus.focused, Some(InputField::TestDuration));
+        if editing.is_some()
+            && (lost_focus
+                || (*interaction == Interaction::None && keys.just_pressed(KeyCode::Escape)))
+        {
+            commands.entity(entity).remove::<TestDurationEditing>();
+            if let Some(&child) = children.first() {
+                if let Ok((mut text, mut buf)) = duration_texts.get_mut(child) {
+                    buf.0 = format!("{:.1}", settings.test_duration);
+                    text.0 = buf.0.clone();
+                }
+            }
+            if !lost_focus {
+                focus.focused = None;
+            }
+            continue;
+        }
+        if editing.is_some() {
+            if let Some(&child) = children.first() {
+                if let Ok((mut text, mut buf)) = duration_texts.get_mut(child) {
+                    process_numeric_input(&keys, &mut buf.0, true);
+                    // 0 или пусто - длительность из сценария
+                    if let Ok(val) = buf.0.parse::<f32>() {
+                        settings.test_duration = val.clamp(0.0, 600.0);
+                    } else if buf.0.is_empty() {
+                        settings.test_duration = 0.0;
+                    }
+                    text.0 = format!("{}|", buf.0);
+                }
+            }
+        }
+    }
+}
+
+// Countdown input box system
+pub fn countdown_input_box_system(
+    mut settings: ResMut<Settings>,
+    mut focus: ResMut<InputFocus>,
+    mut countdown_nodes: Query<
+        (Entity, &Interaction, &Children, Option<&CountdownEditing>),
+        With<CountdownInput>,
+    >,
+    mut countdown_texts: Query<(&mut Text, &mut CountdownBuffer)>,
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    for (entity, interaction, children, editing) in countdown_nodes.iter_mut() {
+        if *interaction == Interaction::Pressed {
+            focus.focused = Some(InputField::Countdown);
+            commands.entity(entity).insert(CountdownEditing);
+            if let Some(&child) = children.first() {
+                if let Ok((_, mut buf)) = countdown_texts.get_mut(child) {
+                    buf.0.clear();
+                }
+            }
+        }
+        let lost_focus = !matches!(focus.focused, Some(InputField::Countdown));
+        if editing.is_some()
+            && (lost_focus
+                || (*interaction == Interaction::None && keys.just_pressed(KeyCode::Escape)))
+        {
+            commands.entity(entity).remove::<CountdownEditing>();
+            if let Some(&child) = children.first() {
+                if let Ok((mut text, mut buf)) = countdown_texts.get_mut(child) {
+                    buf.0 = format!("{:.1}", settings.countdown);
+                    text.0 = buf.0.clone();
+                }
+            }
+            if !lost_focus {
+                focus.focused = None;
+            }
+            continue;
+        }
+        if editing.is_some() {
+            if let Some(&child) = children.first() {
+                if let Ok((mut text, mut buf)) = countdown_texts.get_mut(child) {
+                    process_numeric_input(&keys, &mut buf.0, true);
+                    if let Ok(val) = buf.0.parse::<f32>() {
+                        settings.countdown = val.clamp(0.0, 10.0);
+                    } else if buf.0.is_empty() {
+                        settings.countdown = 0.0;
+                    }
+                    text.0 = format!("{}|", buf.0);
+                }
+            }
+        }
+    }
+}
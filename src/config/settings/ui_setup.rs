@@ -6,6 +6,7 @@ use bevy::prelude::*;
 pub fn setup_settings_ui(
     mut commands: Commands,
     settings: Res<Settings>,
+    scenarios: Res<ScenarioLibrary>,
     asset_server: Res<AssetServer>,
 ) {
     commands.spawn((
@@ -51,7 +52,13 @@ pub fn setup_settings_ui(
     spawn_settings_title(&mut commands, main_container, &asset_server, &settings);
 
     // Cards container
-    spawn_settings_cards_container(&mut commands, main_container, &asset_server, &settings);
+    spawn_settings_cards_container(
+        &mut commands,
+        main_container,
+        &asset_server,
+        &settings,
+        &scenarios,
+    );
 
     // Discord link
     spawn_discord_link(&mut commands, main_container, &asset_server, &settings);
@@ -111,6 +118,7 @@ fn spawn_settings_cards_container(
     parent: Entity,
     asset_server: &AssetServer,
     settings: &Settings,
+    scenarios: &ScenarioLibrary,
 ) {
     let container = commands
         .spawn((Node {
@@ -130,6 +138,7 @@ fn spawn_settings_cards_container(
     spawn_arena_settings_card(commands, container, asset_server, settings);
     spawn_target_settings_card(commands, container, asset_server, settings);
     spawn_lighting_settings_card(commands, container, asset_server, settings);
+    spawn_scenario_card(commands, container, asset_server, settings, scenarios);
 }
 
 fn spawn_mouse_sensitivity_card(
@@ -303,6 +312,75 @@ fn spawn_lighting_settings_card(
     );
 }
 
+fn spawn_scenario_card(
+    commands: &mut Commands,
+    parent: Entity,
+    asset_server: &AssetServer,
+    settings: &Settings,
+    scenarios: &ScenarioLibrary,
+) {
+    let card_builder = CardBuilder::new(asset_server, &settings.font_file);
+    let card = card_builder.spawn_settings_card(commands, parent, "Scenario");
+
+    spawn_section_header(
+        commands,
+        card,
+        asset_server,
+        &settings.font_file,
+        "Scenario",
+    );
+
+    let scenario = scenarios.get(&settings.scenario);
+    let input_builder = InputRowBuilder::new(card, asset_server, &settings.font_file);
+
+    input_builder.spawn_selector_row(
+        commands,
+        "Scenario:",
+        &scenario.name,
+        ScenarioSelector,
+        ScenarioSelectorText,
+    );
+
+    input_builder.spawn_text_input_row(
+        commands,
+        "Seed (0 = random):",
+        settings.seed.to_string(),
+        SeedInput,
+        SeedBuffer(settings.seed.to_string()),
+    );
+
+    input_builder.spawn_numeric_input_row(
+        commands,
+        "Duration, s (0 = scenario):",
+        settings.test_duration,
+        1,
+        TestDurationInput,
+        TestDurationBuffer(format!("{:.1}", settings.test_duration)),
+    );
+
+    input_builder.spawn_numeric_input_row(
+        commands,
+        "Countdown, s:",
+        settings.countdown,
+        1,
+        CountdownInput,
+        CountdownBuffer(format!("{:.1}", settings.countdown)),
+    );
+
+    commands
+        .spawn((
+            Text::new(scenario.description.clone()),
+            TextFont {
+                font: asset_server.load(&settings.font_file),
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(UI_COLORS.text_muted),
+            ScenarioDescriptionText,
+        ))
+        .insert(ChildOf(card));
+}
+
 fn spawn_discord_link(
     commands: &mut Commands,
     parent: Entity,
@@ -10,15 +10,16 @@ pub fn update_camera(
     mut camera_query: Query<&mut Transform, With<PlayerCamera>>,
     settings: Res<Settings>,
 ) {
-    if !test.is_running {
-        return;
-    }
-
+    // Движения читаем всегда: накопленные на паузе иначе дернут камеру после нее
     let mut delta = Vec2::ZERO;
     for motion in mouse_motion.read() {
         delta += motion.delta;
     }
 
+    if !test.is_running || test.paused {
+        return;
+    }
+
     if delta.length_squared() > 0.0 {
         let mouse_sensitivity = settings.mouse_sensitivity();
         test.camera_yaw -= delta.x * mouse_sensitivity;
@@ -35,6 +36,18 @@ pub fn update_camera(
     }
 }
 
+// Направление взгляда -> (yaw, pitch), как их считает update_camera
+pub fn direction_to_yaw_pitch(direction: Vec3) -> Vec2 {
+    let d = direction.normalize_or_zero();
+    Vec2::new((-d.x).atan2(-d.z), d.y.clamp(-1.0, 1.0).asin())
+}
+
+pub fn yaw_pitch_to_direction(angles: Vec2) -> Vec3 {
+    let yaw_quat = Quat::from_axis_angle(Vec3::Y, angles.x);
+    let pitch_quat = Quat::from_axis_angle(Vec3::X, angles.y);
+    yaw_quat * pitch_quat * Vec3::NEG_Z
+}
+
 pub fn apply_fov_to_camera(
     settings: Res<Settings>,
     windows: Query<&Window, With<PrimaryWindow>>,
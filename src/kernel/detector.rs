@@ -0,0 +1,446 @@
+use crate::state::*;
+use crate::stats;
+use bevy::prelude::*;
+use log::warn;
+use std::f32::consts::PI;
+
+// Результат работы детектора: смены направления таргета и найденные реакции
+#[derive(Clone, Debug, Default)]
+pub struct Detection {
+    pub changes: Vec<DirectionChange>,
+    pub reactions: Vec<ReactionMatch>,
+}
+
+// Алгоритм поиска реакций игрока по записанной трассе теста
+pub trait ReactionDetector: Send + Sync {
+    // Уникальное имя, по нему детектор выбирается в конфиге
+    fn name(&self) -> &str;
+
+    fn detect(&self, data: &[DataPoint]) -> Detection;
+}
+
+// Все доступные детекторы; `active` определяет основные метрики теста
+#[derive(Resource)]
+pub struct DetectorRegistry {
+    detectors: Vec<Box<dyn ReactionDetector>>,
+    active: String,
+}
+
+impl Default for DetectorRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            detectors: Vec::new(),
+            active: HEURISTIC_DETECTOR_NAME.to_string(),
+        };
+        registry.register(Box::new(HeuristicDetector::default()));
+        registry
+    }
+}
+
+impl DetectorRegistry {
+    // Детектор с тем же именем заменяется
+    pub fn register(&mut self, detector: Box<dyn ReactionDetector>) {
+        match self
+            .detectors
+            .iter()
+            .position(|d| d.name() == detector.name())
+        {
+            Some(i) => self.detectors[i] = detector,
+            None => self.detectors.push(detector),
+        }
+    }
+
+    // Реестр по умолчанию с параметрами и выбором детектора из настроек
+    pub fn from_settings(settings: &Settings) -> Self {
+        let mut registry = Self::default();
+        registry.register(Box::new(HeuristicDetector::new(
+            settings.heuristic_detector.clone(),
+        )));
+        registry.set_active(&settings.detector);
+        registry
+    }
+
+    pub fn set_active(&mut self, name: &str) {
+        if self.get(name).is_some() {
+            self.active = name.to_string();
+        } else {
+            warn!(
+                "Unknown reaction detector '{}', keeping '{}'",
+                name, self.active
+            );
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ReactionDetector> {
+        self.detectors
+            .iter()
+            .find(|d| d.name() == name)
+            .map(|d| d.as_ref())
+    }
+
+    pub fn active(&self) -> &dyn ReactionDetector {
+        self.get(&self.active)
+            .or_else(|| self.detectors.first().map(|d| d.as_ref()))
+            .expect("detector registry is empty")
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ReactionDetector> {
+        self.detectors.iter().map(|d| d.as_ref())
+    }
+}
+
+// Применяет настройки детекторов из конфига к реестру
+pub fn configure_detectors(settings: Res<Settings>, mut registry: ResMut<DetectorRegistry>) {
+    *registry = DetectorRegistry::from_settings(&settings);
+}
+
+impl DetectorReport {
+    pub fn new(name: &str, detection: &Detection) -> Self {
+        let delays: Vec<f32> = detection.reactions.iter().map(|r| r.delay).collect();
+        Self {
+            name: name.to_string(),
+            average_delay: stats::mean(&delays).unwrap_or(0.0) * 1000.0,
+            median_delay: stats::median(&delays).unwrap_or(0.0) * 1000.0,
+            count_directions: detection.changes.len(),
+            react_directions: delays.len(),
+        }
+    }
+}
+
+pub const HEURISTIC_DETECTOR_NAME: &str = "heuristic";
+
+// Исходный алгоритм: поворот таргета в сглаженном окне и поворот прицела,
+// улучшающий соответствие новому направлению
+#[derive(Default)]
+pub struct HeuristicDetector {
+    pub config: HeuristicDetectorConfig,
+}
+
+impl HeuristicDetector {
+    pub fn new(config: HeuristicDetectorConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl ReactionDetector for HeuristicDetector {
+    fn name(&self) -> &str {
+        HEURISTIC_DETECTOR_NAME
+    }
+
+    fn detect(&self, data: &[DataPoint]) -> Detection {
+        // 1. Находим все значимые смены направления таргета
+        let changes = find_target_direction_changes(data, &self.config);
+
+        // 2. Для каждой смены направления таргета находим соответствующую реакцию игрока
+        let reactions = calculate_reaction_delays(data, &changes, &self.config);
+
+        Detection { changes, reactions }
+    }
+}
+
+fn find_target_direction_changes(
+    data: &[DataPoint],
+    config: &HeuristicDetectorConfig,
+) -> Vec<DirectionChange> {
+    let mut changes = Vec::new();
+    let window_size = config.target_window; // Размер окна для сглаживания
+    let min_angle_change = config.min_target_turn_angle; // Минимальный угол поворота в градусах
+    let min_speed = config.min_target_speed; // Минимальная скорость для анализа
+
+    if data.len() < 2 * window_size + 1 {
+        return changes;
+    }
+
+    for i in window_size..(data.len() - window_size) {
+        // Вычисляем сглаженные направления до и после точки
+        let old_direction = calculate_smoothed_target_direction(data, i - window_size, i);
+        let new_direction = calculate_smoothed_target_direction(data, i, i + window_size);
+
+        // Проверяем, есть ли значимое изменение
+        if old_direction.length() < min_speed || new_direction.length() < min_speed {
+            continue;
+        }
+
+        let old_dir_normalized = old_direction.normalize();
+        let new_dir_normalized = new_direction.normalize();
+
+        // Вычисляем угол между направлениями
+        let dot_product = old_dir_normalized.dot(new_dir_normalized).clamp(-1.0, 1.0);
+        let angle_change = dot_product.acos() * 180.0 / PI;
+
+        if angle_change >= min_angle_change {
+            // Проверяем, что это не дубликат (слишком близко к предыдущему)
+            let is_duplicate = changes.iter().any(|change: &DirectionChange| {
+                (change.time - data[i].time).abs() < config.duplicate_window
+            });
+
+            if !is_duplicate {
+                changes.push(DirectionChange {
+                    time: data[i].time,
+                    target_old_direction: Vec2::new(old_dir_normalized.x, old_dir_normalized.y),
+                    target_new_direction: Vec2::new(new_dir_normalized.x, new_dir_normalized.y),
+                    //is_significant: angle_change >= 90.0, // Очень резкие повороты
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+fn calculate_smoothed_target_direction(
+    data: &[DataPoint],
+    start_idx: usize,
+    end_idx: usize,
+) -> Vec2 {
+    if start_idx >= end_idx || end_idx >= data.len() {
+        return Vec2::ZERO;
+    }
+
+    let mut total_direction = Vec2::ZERO;
+    let mut count = 0;
+
+    // Усредняем направления движения в окне
+    for i in start_idx..(end_idx - 1) {
+        let dt = data[i + 1].time - data[i].time;
+        if dt > 0.001 {
+            let direction = Vec2::new(
+                data[i + 1].target_x - data[i].target_x,
+                data[i + 1].target_y - data[i].target_y,
+            ) / dt;
+
+            if direction.length() > 0.1 {
+                // Фильтруем очень медленные движения
+                total_direction += direction;
+                count += 1;
+            }
+        }
+    }
+
+    if count > 0 {
+        total_direction / count as f32
+    } else {
+        Vec2::ZERO
+    }
+}
+
+fn calculate_reaction_delays(
+    data: &[DataPoint],
+    target_changes: &[DirectionChange],
+    config: &HeuristicDetectorConfig,
+) -> Vec<ReactionMatch> {
+    let mut delays = Vec::new();
+
+    for change in target_changes {
+        // Ищем реакцию игрока в разумном временном окне после смены направления таргета
+        let search_start_time = change.time + config.reaction_search_start;
+        let search_end_time = change.time + config.reaction_search_end;
+
+        if let Some(player_reaction_time) =
+            find_player_reaction(data, change, search_start_time, search_end_time, config)
+        {
+            delays.push(ReactionMatch {
+                change_time: change.time,
+                reaction_time: player_reaction_time,
+                delay: player_reaction_time - change.time,
+            });
+        }
+    }
+
+    delays
+}
+
+fn find_player_reaction(
+    data: &[DataPoint],
+    target_change: &DirectionChange,
+    search_start_time: f32,
+    search_end_time: f32,
+    config: &HeuristicDetectorConfig,
+) -> Option<f32> {
+    // Находим индексы для поиска
+    let start_idx = data.iter().position(|d| d.time >= search_start_time)?;
+    let end_idx = data.iter().rposition(|d| d.time <= search_end_time)?;
+
+    let window_size = config.crosshair_window;
+    let min_angle_change = config.min_crosshair_turn_angle; // Минимальное изменение направления прицела
+
+    if start_idx + window_size >= end_idx {
+        return None;
+    }
+
+    // Вычисляем направление движения прицела до момента смены направления таргета
+    let pre_change_crosshair_direction =
+        calculate_crosshair_direction_before_time(data, target_change.time, config)?;
+
+    // Ищем момент, когда игрок начал менять направление движения прицела
+    for i in start_idx..(end_idx - window_size) {
+        let current_crosshair_direction =
+            calculate_smoothed_crosshair_direction(data, i, i + window_size);
+
+        if current_crosshair_direction.length() < 0.1 {
+            continue;
+        }
+
+        let current_dir_normalized = current_crosshair_direction.normalize();
+        let old_dir_normalized = pre_change_crosshair_direction.normalize();
+
+        // Вычисляем угол изменения направления прицела
+        let dot_product = old_dir_normalized
+            .dot(current_dir_normalized)
+            .clamp(-1.0, 1.0);
+        let angle_change = dot_product.acos() * 180.0 / PI;
+
+        if angle_change >= min_angle_change {
+            // Дополнительно проверяем, что новое направление более соответствует новому направлению таргета
+            let target_alignment = current_dir_normalized.dot(target_change.target_new_direction);
+            let old_target_alignment = old_dir_normalized.dot(target_change.target_new_direction);
+
+            if target_alignment > old_target_alignment + config.min_alignment_gain {
+                // Улучшение соответствия
+                return Some(data[i].time);
+            }
+        }
+    }
+
+    None
+}
+
+// Вычисляет направление движения прицела до определенного момента времени
+fn calculate_crosshair_direction_before_time(
+    data: &[DataPoint],
+    time: f32,
+    config: &HeuristicDetectorConfig,
+) -> Option<Vec2> {
+    let time_window = config.pre_change_window;
+    let start_time = time - time_window;
+
+    let relevant_points: Vec<_> = data
+        .iter()
+        .filter(|d| d.time >= start_time && d.time < time)
+        .collect();
+
+    if relevant_points.len() < 3 {
+        return None;
+    }
+
+    let mut total_direction = Vec2::ZERO;
+    let mut count = 0;
+
+    for i in 0..(relevant_points.len() - 1) {
+        let dt = relevant_points[i + 1].time - relevant_points[i].time;
+        if dt > 0.001 {
+            let direction = Vec2::new(
+                relevant_points[i + 1].crosshair_x - relevant_points[i].crosshair_x,
+                relevant_points[i + 1].crosshair_y - relevant_points[i].crosshair_y,
+            ) / dt;
+
+            if direction.length() > 0.05 {
+                total_direction += direction;
+                count += 1;
+            }
+        }
+    }
+
+    if count > 0 {
+        Some(total_direction / count as f32)
+    } else {
+        None
+    }
+}
+
+// Вычисляет сглаженное направление движения прицела в заданном диапазоне
+fn calculate_smoothed_crosshair_direction(
+    data: &[DataPoint],
+    start_idx: usize,
+    end_idx: usize,
+) -> Vec2 {
+    if start_idx >= end_idx || end_idx >= data.len() {
+        return Vec2::ZERO;
+    }
+
+    let mut total_direction = Vec2::ZERO;
+    let mut count = 0;
+
+    for i in start_idx..(end_idx - 1) {
+        let dt = data[i + 1].time - data[i].time;
+        if dt > 0.001 {
+            let direction = Vec2::new(
+                data[i + 1].crosshair_x - data[i].crosshair_x,
+                data[i + 1].crosshair_y - data[i].crosshair_y,
+            ) / dt;
+
+            if direction.length() > 0.05 {
+                total_direction += direction;
+                count += 1;
+            }
+        }
+    }
+
+    if count > 0 {
+        total_direction / count as f32
+    } else {
+        Vec2::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Таргет идет вправо и в момент turn резко поворачивает вверх. Прицел повторяет
+    // движение с задержкой delay и, как рука, поворачивает не мгновенно, а за 20 мс
+    fn single_turn_trace(turn: f32, delay: f32) -> Vec<DataPoint> {
+        let (right, up) = (Vec2::new(2.0, 0.0), Vec2::new(0.0, 2.0));
+        let mut target = Vec2::ZERO;
+        let mut crosshair = Vec2::ZERO;
+        let mut data = Vec::new();
+        for i in 0..2000 {
+            // Время тика как в test_time, с той же погрешностью f32
+            let time = Duration::from_millis(i).as_secs_f32();
+            data.push(DataPoint {
+                time,
+                target_pos: Vec3::ZERO,
+                crosshair_dir: Vec3::NEG_Z,
+                target_x: target.x,
+                target_y: target.y,
+                camera_pos: Vec3::ZERO,
+                crosshair_x: crosshair.x,
+                crosshair_y: crosshair.y,
+            });
+            target += if time < turn { right } else { up } * 0.001;
+            let progress = ((time - turn - delay) / 0.02).clamp(0.0, 1.0);
+            crosshair += right.lerp(up, progress) * 0.001;
+        }
+        data
+    }
+
+    #[test]
+    fn finds_single_turn_and_reaction() {
+        let detection = HeuristicDetector::default().detect(&single_turn_trace(1.0, 0.15));
+
+        assert_eq!(detection.changes.len(), 1);
+        let change = &detection.changes[0];
+        assert!(
+            (change.time - 1.0).abs() < 0.02,
+            "change at {}",
+            change.time
+        );
+
+        assert_eq!(detection.reactions.len(), 1);
+        let reaction = &detection.reactions[0];
+        assert_eq!(reaction.change_time, change.time);
+        assert!(
+            (reaction.delay - 0.15).abs() < 0.02,
+            "delay {}",
+            reaction.delay
+        );
+    }
+
+    #[test]
+    fn straight_path_has_no_turns() {
+        let detection = HeuristicDetector::default().detect(&single_turn_trace(10.0, 0.15));
+        assert!(detection.changes.is_empty());
+        assert!(detection.reactions.is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::state::*;
+use crate::stats;
+use crate::target::{calculate_angular_error, trace_jumps};
+use bevy::prelude::*;
+
+const DIRECTION_LABELS: [&str; 4] = ["Left", "Right", "Up", "Down"];
+const AXIS_LABELS: [&str; 2] = ["Horizontal", "Vertical"];
+// Угол из нормированных векторов неточен: поворот ровно на границу иначе уходит в группу ниже
+const TURN_ANGLE_TOLERANCE: f32 = 1e-3;
+
+// Смена направления с реакцией игрока (мс) и средней ошибкой до следующей смены
+struct TurnSample<'a> {
+    change: &'a DirectionChange,
+    delay: Option<f32>,
+    error: Option<f32>,
+}
+
+// Группы смен направления: куда ушел таргет, вдоль какой оси развернулся и насколько резко.
+// Асимметрию игрока (например, медленные развороты справа налево) видно по разнице групп
+pub fn analyze_directions(test: &mut ReactionTest) {
+    let errors: Vec<f32> = test
+        .data
+        .iter()
+        .map(|p| calculate_angular_error(p.target_pos - p.camera_pos, p.crosshair_dir))
+        .collect();
+    let changes = &test.direction_changes;
+    let jumps = trace_jumps(&test.target_switches, &test.kills);
+    let samples: Vec<TurnSample> = changes
+        .iter()
+        .enumerate()
+        .map(|(i, change)| {
+            // Ошибка - до следующей смены или до скачка трассы, если он раньше
+            let next_change = changes.get(i + 1).map_or(f32::INFINITY, |next| next.time);
+            let next_jump = jumps
+                .iter()
+                .copied()
+                .find(|&jump| jump > change.time)
+                .unwrap_or(f32::INFINITY);
+            let end = next_change.min(next_jump);
+            let from = test.data.partition_point(|p| p.time < change.time);
+            let to = test.data.partition_point(|p| p.time < end);
+            TurnSample {
+                change,
+                delay: test
+                    .reactions
+                    .iter()
+                    .find(|r| r.change_time == change.time)
+                    .map(|r| r.delay * 1000.0),
+                error: stats::mean(&errors[from..to]),
+            }
+        })
+        .collect();
+
+    let breakdown = DirectionBreakdown {
+        by_direction: group(&samples, &DIRECTION_LABELS.map(String::from), |s| {
+            Some(direction_index(s.change.target_new_direction))
+        }),
+        by_axis: group(&samples, &AXIS_LABELS.map(String::from), |s| {
+            reversal_axis(s.change)
+        }),
+        by_turn: group(&samples, &turn_labels(), |s| turn_index(s.change)),
+    };
+    test.direction_breakdown = breakdown;
+}
+
+fn group(
+    samples: &[TurnSample],
+    labels: &[String],
+    key: impl Fn(&TurnSample) -> Option<usize>,
+) -> Vec<DirectionBin> {
+    let mut groups: Vec<Vec<&TurnSample>> = vec![Vec::new(); labels.len()];
+    for sample in samples {
+        if let Some(index) = key(sample) {
+            groups[index].push(sample);
+        }
+    }
+
+    groups
+        .iter()
+        .zip(labels)
+        .filter(|(group, _)| !group.is_empty())
+        .map(|(group, label)| {
+            let delays: Vec<f32> = group.iter().filter_map(|s| s.delay).collect();
+            let errors: Vec<f32> = group.iter().filter_map(|s| s.error).collect();
+            DirectionBin {
+                label: label.clone(),
+                changes: group.len(),
+                reactions: delays.len(),
+                average_delay: stats::mean(&delays).unwrap_or(0.0),
+                median_delay: stats::median(&delays).unwrap_or(0.0),
+                average_error: stats::mean(&errors).unwrap_or(0.0),
+            }
+        })
+        .collect()
+}
+
+// По преобладающей оси нового направления; x - вправо, y - вверх в плоскости экрана
+fn direction_index(direction: Vec2) -> usize {
+    if direction.x.abs() >= direction.y.abs() {
+        if direction.x < 0.0 {
+            0
+        } else {
+            1
+        }
+    } else if direction.y > 0.0 {
+        2
+    } else {
+        3
+    }
+}
+
+// Ось разворота - та, вдоль которой скорость изменилась сильнее
+fn reversal_axis(change: &DirectionChange) -> Option<usize> {
+    if change.target_old_direction == Vec2::ZERO {
+        return None;
+    }
+    let delta = (change.target_new_direction - change.target_old_direction).abs();
+    Some(if delta.x >= delta.y { 0 } else { 1 })
+}
+
+fn turn_index(change: &DirectionChange) -> Option<usize> {
+    if change.target_old_direction == Vec2::ZERO {
+        return None;
+    }
+    let angle = change
+        .target_old_direction
+        .angle_to(change.target_new_direction)
+        .abs()
+        .to_degrees();
+    Some(
+        TURN_SHARPNESS_BOUNDS
+            .iter()
+            .filter(|bound| angle + TURN_ANGLE_TOLERANCE >= **bound)
+            .count(),
+    )
+}
+
+// "< 45°", "45-90°", ..., "> 135°"
+fn turn_labels() -> Vec<String> {
+    let bounds = TURN_SHARPNESS_BOUNDS;
+    let mut labels = vec![format!("< {:.0}°", bounds[0])];
+    labels.extend(
+        bounds
+            .windows(2)
+            .map(|pair| format!("{:.0}-{:.0}°", pair[0], pair[1])),
+    );
+    labels.push(format!("> {:.0}°", bounds[bounds.len() - 1]));
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(degrees: f32) -> DirectionChange {
+        let old = Vec2::new(1.0, 0.0);
+        DirectionChange {
+            time: 0.0,
+            target_old_direction: old,
+            target_new_direction: Vec2::from_angle(degrees.to_radians()).rotate(old),
+        }
+    }
+
+    fn turn_label(degrees: f32) -> String {
+        turn_labels()[turn_index(&turn(degrees)).unwrap()].clone()
+    }
+
+    #[test]
+    fn turns_on_bounds_go_to_the_upper_bucket() {
+        assert_eq!(turn_label(45.0), "45-90°");
+        assert_eq!(turn_label(90.0), "90-135°");
+        assert_eq!(turn_label(135.0), "> 135°");
+        // Знак поворота не важен
+        assert_eq!(turn_label(-90.0), "90-135°");
+    }
+
+    #[test]
+    fn turns_between_bounds() {
+        assert_eq!(turn_label(30.0), "< 45°");
+        assert_eq!(turn_label(60.0), "45-90°");
+        assert_eq!(turn_label(120.0), "90-135°");
+        assert_eq!(turn_label(180.0), "> 135°");
+    }
+
+    #[test]
+    fn old_record_has_no_turn() {
+        let mut change = turn(90.0);
+        change.target_old_direction = Vec2::ZERO;
+        assert_eq!(turn_index(&change), None);
+    }
+}
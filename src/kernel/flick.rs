@@ -0,0 +1,182 @@
+use crate::camera::{direction_to_yaw_pitch, yaw_pitch_to_direction};
+use crate::detector::DetectorRegistry;
+use crate::state::*;
+use crate::stats;
+use crate::target::{finish_test, ray_hits_target, record_data_point, test_time};
+use bevy::audio::{AudioPlayer, PlaybackSettings};
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::PI;
+
+// Попытка без попадания завершается через это время, секунды
+const FLICK_TRIAL_TIMEOUT: f32 = 3.0;
+// Поворот прицела, после которого считаем, что игрок начал движение, градусы
+const FLICK_MOVE_THRESHOLD: f32 = 0.5;
+// Таргеты не появляются выше/ниже этого угла, градусы
+const FLICK_MAX_PITCH: f32 = 60.0;
+
+// Flick-режим: статичный таргет, выстрел по кнопке мыши, одна попытка до попадания
+#[allow(clippy::too_many_arguments)]
+pub fn update_flick(
+    mut test: ResMut<ReactionTest>,
+    mut target_query: Query<&mut Transform, (With<Target>, Without<PlayerCamera>)>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    time_fixed: Res<Time<Fixed>>,
+    mut mouse_events: EventReader<MouseButtonInput>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+    game_audio: Res<GameAudio>,
+    mut finished_events: EventWriter<TestFinishedEvent>,
+    detectors: Res<DetectorRegistry>,
+) {
+    // Клики читаем всегда, чтобы старые не сработали в следующем тесте.
+    // EventReader отдает каждый клик один раз, даже если за кадр несколько тиков
+    let shots = mouse_events
+        .read()
+        .filter(|e| e.button == settings.key_shoot && e.state == ButtonState::Pressed)
+        .count();
+
+    if !test.is_running || test.scenario.mode != TestMode::Flick {
+        return;
+    }
+
+    let Some(current_time) = test_time(&test, &time_fixed) else {
+        return;
+    };
+    if current_time >= test.scenario.test_duration {
+        if !test.test_completed {
+            finish_test(&mut test, &detectors);
+            finished_events.write(TestFinishedEvent);
+        }
+        return;
+    }
+
+    let cam_pos = camera_query
+        .single()
+        .map(|t| t.translation)
+        .unwrap_or(test.start_cam_pos);
+
+    if test.flick_trial.is_none() {
+        spawn_flick_target(&mut test, current_time, cam_pos);
+    }
+    let Some(mut trial) = test.flick_trial.take() else {
+        return;
+    };
+
+    // Начало движения и продвижение прицела вдоль линии флика
+    let crosshair_angles = direction_to_yaw_pitch(test.crosshair_direction);
+    let moved = (crosshair_angles - trial.start_angles).length();
+    if trial.first_move.is_none() && moved >= FLICK_MOVE_THRESHOLD.to_radians() {
+        trial.first_move = Some(current_time - trial.start_time);
+    }
+    let axis = trial.target_angles - trial.start_angles;
+    let axis_length = axis.length();
+    if axis_length > f32::EPSILON {
+        let progress = (crosshair_angles - trial.start_angles).dot(axis / axis_length);
+        trial.max_progress = trial.max_progress.max(progress);
+    }
+
+    let mut hit = false;
+    for _ in 0..shots {
+        if ray_hits_target(
+            cam_pos,
+            test.crosshair_direction,
+            test.target_position,
+            test.scenario.target_size,
+        ) {
+            hit = true;
+            test.hits += 1;
+            commands.spawn((
+                AudioPlayer::new(game_audio.hit_sound.clone()),
+                PlaybackSettings::DESPAWN,
+            ));
+            break;
+        }
+        test.misses += 1;
+        trial.misses += 1;
+    }
+
+    record_data_point(&mut test, cam_pos, current_time);
+
+    let elapsed = current_time - trial.start_time;
+    if !hit && elapsed < FLICK_TRIAL_TIMEOUT {
+        test.flick_trial = Some(trial);
+    } else {
+        let start_dir = yaw_pitch_to_direction(trial.start_angles);
+        let target_dir = yaw_pitch_to_direction(trial.target_angles);
+        let target_width = 2.0 * (test.scenario.target_size / test.target_distance).atan();
+        test.flick_trials.push(FlickTrial {
+            start_time: trial.start_time,
+            distance: start_dir.angle_between(target_dir).to_degrees(),
+            target_width: target_width.to_degrees(),
+            time_to_first_move: trial.first_move,
+            time_to_hit: hit.then_some(elapsed),
+            overshoot: (trial.max_progress - axis_length).max(0.0).to_degrees(),
+            misses: trial.misses,
+        });
+
+        if test.flick_trials.len() >= test.scenario.flick_trials as usize {
+            finish_test(&mut test, &detectors);
+            finished_events.write(TestFinishedEvent);
+            return;
+        }
+        spawn_flick_target(&mut test, current_time, cam_pos);
+    }
+
+    if let Ok(mut target_transform) = target_query.single_mut() {
+        target_transform.translation = test.target_position;
+    }
+}
+
+// Новый таргет на случайном угловом расстоянии от текущего положения прицела
+fn spawn_flick_target(test: &mut ReactionTest, current_time: f32, cam_pos: Vec3) {
+    let start_angles = direction_to_yaw_pitch(test.crosshair_direction);
+    let direction = test.rng.gen_range(0.0..2.0 * PI);
+    let distance = test
+        .rng
+        .gen_range(test.scenario.flick_min_angle..=test.scenario.flick_max_angle)
+        .to_radians();
+    let max_pitch = FLICK_MAX_PITCH.to_radians();
+    let target_angles = Vec2::new(
+        start_angles.x + distance * direction.cos(),
+        (start_angles.y + distance * direction.sin()).clamp(-max_pitch, max_pitch),
+    );
+
+    test.target_position = cam_pos + yaw_pitch_to_direction(target_angles) * test.target_distance;
+    test.flick_trial = Some(FlickTrialState {
+        start_time: current_time,
+        start_angles,
+        target_angles,
+        first_move: None,
+        max_progress: 0.0,
+        misses: 0,
+    });
+}
+
+// Средние по попыткам и связь времени попадания с трудностью по Фиттсу
+pub fn analyze_flick(test: &mut ReactionTest) {
+    let trials = &test.flick_trials;
+    let first_moves: Vec<f32> = trials.iter().filter_map(|t| t.time_to_first_move).collect();
+    let overshoots: Vec<f32> = trials.iter().map(|t| t.overshoot).collect();
+
+    let hits: Vec<&FlickTrial> = trials.iter().filter(|t| t.time_to_hit.is_some()).collect();
+    let times: Vec<f32> = hits.iter().filter_map(|t| t.time_to_hit).collect();
+    let difficulty: Vec<f32> = hits
+        .iter()
+        .map(|t| (t.distance / t.target_width + 1.0).log2())
+        .collect();
+    let (intercept, slope) = stats::linear_fit(&difficulty, &times).unwrap_or((0.0, 0.0));
+
+    test.flick_summary = FlickSummary {
+        trials: trials.len(),
+        hits: hits.len(),
+        average_first_move: stats::mean(&first_moves).unwrap_or(0.0) * 1000.0,
+        average_time_to_hit: stats::mean(&times).unwrap_or(0.0) * 1000.0,
+        average_overshoot: stats::mean(&overshoots).unwrap_or(0.0),
+        fitts_correlation: stats::pearson(&difficulty, &times).unwrap_or(0.0),
+        fitts_intercept: intercept * 1000.0,
+        fitts_slope: slope * 1000.0,
+    };
+}
@@ -1,17 +1,23 @@
 use crate::fresnel::*;
+use crate::ghost::next_run;
 use crate::state::*;
 use crate::user_interface::ui::UI_COLORS;
-use bevy::math::primitives::Sphere as SpherePrim;
+use crate::utils::spawn_targets;
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
+use bevy::window::{PrimaryWindow, WindowFocused};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::f32::consts::PI;
+use std::time::Duration;
 
+#[allow(clippy::too_many_arguments)]
 pub fn setup_game_scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut extended_materials: ResMut<Assets<ExtendedMaterial>>,
     settings: Res<Settings>,
+    scenarios: Res<ScenarioLibrary>,
     windows: Query<&Window, With<PrimaryWindow>>,
     asset_server: Res<AssetServer>,
 ) {
@@ -25,6 +31,11 @@ pub fn setup_game_scene(
 
     let game_audio = GameAudio {
         hit_sound: asset_server.load("hit.ogg"),
+        stimulus_sounds: settings
+            .stimulus_sound_files
+            .iter()
+            .map(|file| asset_server.load(file.clone()))
+            .collect(),
     };
     commands.insert_resource(game_audio);
 
@@ -139,57 +150,16 @@ pub fn setup_game_scene(
         ArenaWall,
     ));
 
-    // Target sphere (spawn at center initially; will be positioned at fixed distance on test start)
-    if settings.fresnel_enabled {
-        // Use extended material with Fresnel effect
-        let fresnel_color = settings.get_fresnel_color();
-        let fresnel_material = FresnelMaterial {
-            fresnel_color: Vec4::new(
-                fresnel_color.to_linear().red,
-                fresnel_color.to_linear().green,
-                fresnel_color.to_linear().blue,
-                settings.fresnel_intensity, // intensity in alpha channel
-            ),
-            fresnel_params: Vec4::new(
-                settings.fresnel_power,
-                1.0, // enabled
-                0.0, // unused
-                0.0, // unused
-            ),
-        };
-
-        commands.spawn((
-            Mesh3d(meshes.add(Mesh::from(SpherePrim {
-                radius: TARGET_SIZE,
-            }))),
-            MeshMaterial3d(extended_materials.add(ExtendedMaterial {
-                base: StandardMaterial {
-                    base_color: settings.get_target_color(),
-                    unlit: false,
-                    ..default()
-                },
-                extension: fresnel_material,
-            })),
-            Transform::from_translation(Vec3::ZERO),
-            Visibility::default(),
-            Target,
-        ));
-    } else {
-        // Use standard material
-        commands.spawn((
-            Mesh3d(meshes.add(Mesh::from(SpherePrim {
-                radius: TARGET_SIZE,
-            }))),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: settings.get_target_color(),
-                unlit: false,
-                ..default()
-            })),
-            Transform::from_translation(Vec3::ZERO),
-            Visibility::default(),
-            Target,
-        ));
-    }
+    // Target spheres (spawn at center initially; will be positioned at fixed distance on test start)
+    spawn_targets(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut extended_materials,
+        &settings,
+        &scenarios.get(&settings.scenario),
+        &[],
+    );
 
     // Camera with FOV setting
     let aspect_ratio = if let Ok(window) = windows.single() {
@@ -283,6 +253,37 @@ pub fn setup_game_ui(
             ));
         });
 
+    // История результатов (по клавише H)
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(60.0),
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            BackgroundColor(UI_COLORS.background),
+            BorderRadius::all(Val::Px(12.0)),
+            Visibility::Hidden,
+            // Поверх экрана результатов
+            GlobalZIndex(2),
+            GameUI,
+            HistoryPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(""),
+                TextFont {
+                    font: asset_server.load(&settings.font_file),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(UI_COLORS.text_primary),
+                HistoryText,
+            ));
+        });
+
     // FPS индикатор
     commands
         .spawn((
@@ -404,6 +405,7 @@ pub fn setup_game_ui(
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn cleanup_game_scene(
     mut commands: Commands,
     arena_query: Query<Entity, With<Arena>>,
@@ -412,6 +414,8 @@ pub fn cleanup_game_scene(
     ui_query: Query<Entity, With<GameUI>>,
     crosshair_query: Query<Entity, With<Crosshair>>,
     light_query: Query<Entity, With<DirectionalLightEntity>>,
+    replay_query: Query<Entity, With<ReplayMarker>>,
+    ghost_query: Query<Entity, With<GhostMarker>>,
 ) {
     for entity in arena_query
         .iter()
@@ -420,6 +424,8 @@ pub fn cleanup_game_scene(
         .chain(ui_query.iter())
         .chain(crosshair_query.iter())
         .chain(light_query.iter())
+        .chain(replay_query.iter())
+        .chain(ghost_query.iter())
     {
         commands.entity(entity).despawn();
     }
@@ -464,6 +470,7 @@ pub fn update_lighting(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn game_input_system(
     keys: Res<ButtonInput<KeyCode>>,
     mut test: ResMut<ReactionTest>,
@@ -472,56 +479,186 @@ pub fn game_input_system(
     mut camera_query: Query<&mut Transform, With<PlayerCamera>>,
     mut target_query: Query<&mut Transform, (With<Target>, Without<PlayerCamera>)>,
     settings: Res<Settings>,
+    scenarios: Res<ScenarioLibrary>,
+    replay: Res<ReplayViewer>,
+    mut ghost: ResMut<GhostRace>,
 ) {
+    // Клавишами просмотра записи управляет replay_input_system
+    if replay.active {
+        return;
+    }
     // Quick restart
     if keys.just_pressed(settings.key_restart) {
         reset_test(&mut test);
-        if let Ok(mut cam) = camera_query.get_single_mut() {
-            cam.rotation = Quat::IDENTITY;
-            start_test(&mut test, &time_fixed);
-            test.start_cam_pos = cam.translation;
-            let forward = cam.forward();
-            test.start_cam_forward = *forward;
-            test.target_position =
-                test.start_cam_pos + test.start_cam_forward.normalize() * test.target_distance;
-            if let Ok(mut t) = target_query.get_single_mut() {
-                t.translation = test.target_position;
-            }
+        launch_test(
+            &mut test,
+            &time_fixed,
+            &settings,
+            &scenarios,
+            &mut ghost,
+            &mut camera_query,
+            &mut target_query,
+        );
+    }
+
+    if keys.just_pressed(settings.key_start) && test.paused {
+        resume_test(&mut test, &time_fixed);
+    } else if keys.just_pressed(settings.key_start) && !test.is_running {
+        launch_test(
+            &mut test,
+            &time_fixed,
+            &settings,
+            &scenarios,
+            &mut ghost,
+            &mut camera_query,
+            &mut target_query,
+        );
+    }
+
+    // ESC посреди теста ставит паузу; ESC на паузе - в настройки, тест сбрасывается
+    if keys.just_pressed(settings.key_settings) {
+        if test.is_running && !test.paused {
+            pause_test(&mut test, &time_fixed);
+        } else {
+            next_state.set(AppState::Settings);
         }
     }
+}
 
-    if keys.just_pressed(settings.key_start) && !test.is_running {
-        start_test(&mut test, &time_fixed);
-        if let Ok(mut cam) = camera_query.get_single_mut() {
-            cam.rotation = Quat::IDENTITY;
-            test.start_cam_pos = cam.translation;
-            let forward = cam.forward();
-            test.start_cam_forward = *forward;
-            test.target_position =
-                test.start_cam_pos + test.start_cam_forward.normalize() * test.target_distance;
-            if let Ok(mut t) = target_query.get_single_mut() {
-                t.translation = test.target_position;
-            }
+// Новый тест: камера в исходном положении, таргет прямо под прицелом
+pub fn launch_test(
+    test: &mut ReactionTest,
+    time_fixed: &Time<Fixed>,
+    settings: &Settings,
+    scenarios: &ScenarioLibrary,
+    ghost: &mut GhostRace,
+    camera_query: &mut Query<&mut Transform, With<PlayerCamera>>,
+    target_query: &mut Query<&mut Transform, (With<Target>, Without<PlayerCamera>)>,
+) {
+    let (scenario, seed, run_settings) = next_run(settings, scenarios, ghost);
+    start_test(test, time_fixed, scenario, seed, run_settings);
+    if let Ok(mut cam) = camera_query.get_single_mut() {
+        cam.rotation = Quat::IDENTITY;
+        test.start_cam_pos = cam.translation;
+        let forward = cam.forward();
+        test.start_cam_forward = *forward;
+        test.target_position =
+            test.start_cam_pos + test.start_cam_forward.normalize() * test.target_distance;
+        if let Ok(mut t) = target_query.get_single_mut() {
+            t.translation = test.target_position;
         }
     }
+}
 
-    if keys.just_pressed(settings.key_settings) {
-        next_state.set(AppState::Settings);
+pub fn pause_test(test: &mut ReactionTest, time_fixed: &Time<Fixed>) {
+    if !test.is_running || test.paused {
+        return;
     }
+    test.paused = true;
+    test.paused_at = time_fixed.elapsed();
+    // Стимул и flick-таргет на паузе видны: после нее такая попытка дала бы мусорное время.
+    // Начатая попытка отбрасывается, после паузы системы режимов начнут новую
+    test.reaction_trial = None;
+    test.flick_trial = None;
 }
 
-pub fn start_test(test: &mut ReactionTest, time_fixed: &Time<Fixed>) {
+// Сдвигает начало теста на длительность паузы: время теста продолжается с того же места
+pub fn resume_test(test: &mut ReactionTest, time_fixed: &Time<Fixed>) {
+    if !test.paused {
+        return;
+    }
+    let paused_for = time_fixed.elapsed().saturating_sub(test.paused_at);
+    let time = test
+        .paused_at
+        .saturating_sub(test.fixed_start)
+        .as_secs_f32();
+    test.fixed_start += paused_for;
+    test.start_time += paused_for.as_secs_f32();
+    test.paused = false;
+    test.pauses.push(TestPause {
+        time,
+        duration: paused_for.as_secs_f32(),
+    });
+}
+
+// Потеря фокуса окна (alt-tab) ставит тест на паузу, чтобы не писать мусор
+pub fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    mut test: ResMut<ReactionTest>,
+    time_fixed: Res<Time<Fixed>>,
+) {
+    for event in focus_events.read() {
+        if !event.focused {
+            pause_test(&mut test, &time_fixed);
+        }
+    }
+}
+
+pub fn start_test(
+    test: &mut ReactionTest,
+    time_fixed: &Time<Fixed>,
+    scenario: Scenario,
+    seed: u64,
+    run_settings: RunSettings,
+) {
+    test.scenario = scenario;
+    if run_settings.test_duration > 0.0 {
+        test.scenario.test_duration = run_settings.test_duration;
+    }
+    test.seed = seed;
+    test.rng = StdRng::seed_from_u64(seed);
+    test.run_settings = run_settings;
     test.is_running = true;
     test.test_completed = false;
-    test.start_time = time_fixed.elapsed().as_secs_f32();
+    // Во время обратного отсчета таргет виден, но тест еще не идет
+    let countdown = Duration::from_secs_f32(run_settings.countdown.max(0.0));
+    test.start_time = (time_fixed.elapsed() + countdown).as_secs_f32();
+    test.fixed_start = time_fixed.elapsed() + countdown;
+    test.paused = false;
+    test.pauses.clear();
     test.data.clear();
+    // Счетчики и метрики прошлого запуска
+    test.hits = 0;
+    test.misses = 0;
+    test.last_shot_time = 0.0;
+    test.count_directions = 0;
+    test.react_directions = 0;
+    test.average_delay = 0.0;
+    test.median_delay = 0.0;
+    test.delay_stats = DelayStats::default();
+    test.xcorr_delay = 0.0;
+    test.xcorr_correlation = 0.0;
+    test.xcorr_segment_delays.clear();
+    test.xcorr_segment_count = 0;
+    test.rms_distance = 0.0;
+    test.peak_angular_error = 0.0;
+    test.detector_reports.clear();
+    test.direction_changes.clear();
+    test.reactions.clear();
+    test.flick_trial = None;
+    test.flick_trials.clear();
+    test.flick_summary = FlickSummary::default();
+    test.reaction_trial = None;
+    test.reaction_trials.clear();
+    test.reaction_summary = ReactionTrialSummary::default();
 
     test.crosshair_direction = Vec3::NEG_Z;
     // Place target directly under crosshair at a fixed distance along -Z
     test.target_position = test.crosshair_direction.normalize() * test.target_distance;
     // Initialize lateral velocity; movement will be constrained to sphere of radius target_distance
-    test.target_velocity = Vec3::new(1.0, 0.5, 0.5).normalize() * TARGET_SPEED;
-    test.next_direction_change = 0.1;
+    test.target_velocity = Vec3::new(1.0, 0.5, 0.5).normalize() * test.scenario.target_speed;
+    test.targets.clear();
+    test.active_target = 0;
+    test.target_switches.clear();
+    test.switch_summary = SwitchSummary::default();
+    test.size_bins.clear();
+    test.direction_breakdown = DirectionBreakdown::default();
+    test.staircase = None;
+    test.staircase_steps.clear();
+    test.staircase_summary = StaircaseSummary::default();
+    test.damage_dealt = 0.0;
+    test.kills.clear();
+    test.kill_summary = KillSummary::default();
 
     test.camera_yaw = 0.0;
     test.camera_pitch = 0.0;
@@ -529,6 +666,7 @@ pub fn start_test(test: &mut ReactionTest, time_fixed: &Time<Fixed>) {
 
 pub fn reset_test(test: &mut ReactionTest) {
     test.is_running = false;
+    test.paused = false;
     test.test_completed = false;
     test.start_time = 0.0;
     test.hits = 0;
@@ -537,9 +675,9 @@ pub fn reset_test(test: &mut ReactionTest) {
     test.data.clear();
     test.crosshair_direction = Vec3::NEG_Z;
     test.target_position = Vec3::ZERO;
-    test.target_velocity = Vec3::new(1.0, 0.5, 0.5).normalize() * TARGET_SPEED;
-    test.next_direction_change = 0.1;
-    test.change_interval = 0.1;
+    test.target_velocity = Vec3::new(1.0, 0.5, 0.5).normalize() * test.scenario.target_speed;
+    test.targets.clear();
+    test.active_target = 0;
     test.camera_yaw = 0.0;
     test.camera_pitch = 0.0;
 }
@@ -0,0 +1,195 @@
+use crate::export::RunExport;
+use crate::state::*;
+use crate::target::{ray_hits_target, test_time};
+use crate::user_interface::ui::UI_COLORS;
+use bevy::prelude::*;
+use log::warn;
+
+// Маркер прицела призрака: радиус относительно таргета и прозрачность
+const GHOST_MARKER_SCALE: f32 = 0.5;
+const GHOST_ALPHA: f32 = 0.35;
+
+// Маркер призрака; без камеры, чтобы не пересекаться с ее Transform
+type GhostMarkers<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static mut Transform, &'static mut Visibility),
+    (With<GhostMarker>, Without<PlayerCamera>),
+>;
+
+// Сценарий, seed и настройки следующего теста: в гонке - из записи призрака
+pub fn next_run(
+    settings: &Settings,
+    scenarios: &ScenarioLibrary,
+    ghost: &mut GhostRace,
+) -> (Scenario, u64, RunSettings) {
+    let mut run_settings = settings.run_settings();
+    if ghost.is_active() {
+        ghost.start_race();
+        // Длительность уже в сценарии записи
+        run_settings.test_duration = 0.0;
+        return (ghost.scenario.clone(), ghost.seed, run_settings);
+    }
+    ghost.racing = false;
+    (
+        scenarios.get(&settings.scenario),
+        settings.next_run_seed(),
+        run_settings,
+    )
+}
+
+// Призрак есть только у теста слежения, где путь таргета задан seed: в остальных режимах
+// таргет зависит от кликов игрока, лестница меняет скорость по его результату,
+// а респаун убитого таргета тратит случайные числа генератора
+pub fn check_ghost_scenario(scenario: &Scenario) -> Result<(), String> {
+    if scenario.mode != TestMode::Tracking {
+        return Err(format!(
+            "ghost racing needs a tracking run, got {:?}",
+            scenario.mode
+        ));
+    }
+    if scenario.staircase.is_some() {
+        return Err("ghost racing can't follow a run with an adaptive staircase".to_string());
+    }
+    if scenario.target_health > 0.0 {
+        return Err("ghost racing can't follow a run with target health".to_string());
+    }
+    Ok(())
+}
+
+pub fn ghost_from_run(title: String, run: &RunExport) -> Result<GhostRace, String> {
+    check_ghost_scenario(&run.scenario)?;
+    if run.data.is_empty() {
+        return Err("run has no recorded ticks".to_string());
+    }
+    Ok(GhostRace::new(
+        title,
+        run.scenario.clone(),
+        run.seed,
+        run.data.clone(),
+    ))
+}
+
+// На экране результатов G делает последний тест призраком, повторное нажатие убирает призрака
+pub fn ghost_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    test: Res<ReactionTest>,
+    replay: Res<ReplayViewer>,
+    mut ghost: ResMut<GhostRace>,
+) {
+    if replay.active || test.is_running || !keys.just_pressed(settings.key_ghost) {
+        return;
+    }
+    if ghost.is_active() {
+        *ghost = GhostRace::default();
+    } else if test.test_completed {
+        if let Err(e) = check_ghost_scenario(&test.scenario) {
+            warn!("{}", e);
+            return;
+        }
+        *ghost = GhostRace::new(
+            format!("last run, seed {}", test.seed),
+            test.scenario.clone(),
+            test.seed,
+            test.data.clone(),
+        );
+    }
+}
+
+// Ставит маркер призрака туда, куда он целился в это же время теста, и считает счет гонки
+#[allow(clippy::too_many_arguments)]
+pub fn update_ghost(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut ghost: ResMut<GhostRace>,
+    test: Res<ReactionTest>,
+    time_fixed: Res<Time<Fixed>>,
+    replay: Res<ReplayViewer>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    mut marker_query: GhostMarkers,
+) {
+    if !ghost.is_active() {
+        for (entity, _, _) in marker_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    if marker_query.is_empty() {
+        commands.spawn((
+            Mesh3d(meshes.add(Sphere::new(ghost.scenario.target_size * GHOST_MARKER_SCALE))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: UI_COLORS.primary.with_alpha(GHOST_ALPHA),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })),
+            Transform::default(),
+            Visibility::Hidden,
+            GhostMarker,
+        ));
+        return;
+    }
+
+    let ghost = &mut *ghost;
+    if ghost.racing {
+        count_on_target(ghost, &test, time_fixed.timestep().as_secs_f32());
+    }
+
+    let time =
+        test_time(&test, &time_fixed).filter(|_| test.is_running && ghost.racing && !replay.active);
+    let cam_pos = camera_query
+        .single()
+        .map_or(test.start_cam_pos, |t| t.translation);
+    for (_, mut transform, mut visibility) in marker_query.iter_mut() {
+        let Some(time) = time else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let index = ghost
+            .data
+            .partition_point(|p| p.time < time)
+            .min(ghost.data.len() - 1);
+        // На дистанции живого таргета, чуть ближе его поверхности, чтобы таргет не закрывал маркер
+        let distance = ((test.target_position - cam_pos).length() - test.scenario.target_size)
+            .max(test.scenario.target_size);
+        transform.translation =
+            cam_pos + ghost.data[index].crosshair_dir.normalize_or_zero() * distance;
+        *visibility = Visibility::Visible;
+    }
+}
+
+// Досчитывает новые тики игрока и тики призрака до того же момента теста; разминка не в счет
+fn count_on_target(ghost: &mut GhostRace, test: &ReactionTest, tick: f32) {
+    let warmup = ghost.scenario.warmup;
+    let on_target = |point: &DataPoint, radius: f32| {
+        point.time >= warmup
+            && ray_hits_target(
+                point.camera_pos,
+                point.crosshair_dir,
+                point.target_pos,
+                radius,
+            )
+    };
+
+    for point in test.data.iter().skip(ghost.live_index) {
+        if on_target(point, test.scenario.target_size) {
+            ghost.live_on_target += tick;
+        }
+    }
+    ghost.live_index = ghost.live_index.max(test.data.len());
+
+    let Some(now) = test.data.last().map(|p| p.time) else {
+        return;
+    };
+    while let Some(point) = ghost.data.get(ghost.ghost_index) {
+        if point.time > now {
+            break;
+        }
+        if on_target(point, ghost.scenario.target_size) {
+            ghost.ghost_on_target += tick;
+        }
+        ghost.ghost_index += 1;
+    }
+}
@@ -0,0 +1,79 @@
+use crate::detector::{configure_detectors, DetectorRegistry};
+use crate::flick::update_flick;
+use crate::game::start_test;
+use crate::reaction::update_reaction;
+use crate::state::*;
+use crate::target::update_target;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+// Позиция камеры такая же, как в setup_game_scene
+pub const HEADLESS_CAMERA_POS: Vec3 = Vec3::new(0.0, 0.0, 10.0);
+
+// Приложение без окна и рендера: только движение таргета на 1 кГц и анализ.
+// Тест уже запущен, остается крутить app.update() через run_headless_test
+pub fn build_headless_app(settings: Settings, scenario: Scenario, seed: u64) -> App {
+    let run_settings = settings.run_settings();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(Time::<Fixed>::from_seconds(1.0 / 1000.0))
+        // Каждый update - ровно один тик, независимо от реального времени
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(1)))
+        .insert_resource(settings)
+        .insert_resource(GameAudio {
+            hit_sound: Handle::default(),
+            stimulus_sounds: Vec::new(),
+        })
+        .init_resource::<ReactionTest>()
+        .init_resource::<DetectorRegistry>()
+        .add_event::<TestFinishedEvent>()
+        // Без InputPlugin кликов не будет, но update_flick и update_reaction должны запускаться
+        .add_event::<MouseButtonInput>()
+        .add_systems(Startup, configure_detectors)
+        .add_systems(FixedUpdate, (update_target, update_flick, update_reaction));
+
+    // update_target ищет таргет и камеру по компонентам
+    app.world_mut().spawn((
+        PlayerCamera,
+        Transform::from_translation(HEADLESS_CAMERA_POS),
+    ));
+    app.world_mut()
+        .spawn((Target, TargetIndex(0), Transform::default()));
+
+    app.world_mut()
+        .resource_scope(|world, mut test: Mut<ReactionTest>| {
+            start_test(
+                &mut test,
+                world.resource::<Time<Fixed>>(),
+                scenario,
+                seed,
+                run_settings,
+            );
+            test.start_cam_pos = HEADLESS_CAMERA_POS;
+            test.start_cam_forward = Vec3::NEG_Z;
+            test.target_position =
+                test.start_cam_pos + test.start_cam_forward * test.target_distance;
+        });
+
+    app
+}
+
+// Прогоняет тест до конца; false, если тест так и не завершился
+pub fn run_headless_test(app: &mut App) -> bool {
+    let test = app.world().resource::<ReactionTest>();
+    // Обратный отсчет тоже идет тиками
+    let duration = test.scenario.test_duration + test.run_settings.countdown;
+    // Запас на первые кадры, пока не накопилось время для FixedUpdate
+    let max_updates = (duration * 1000.0) as usize + 1000;
+
+    for _ in 0..max_updates {
+        app.update();
+        if app.world().resource::<ReactionTest>().test_completed {
+            return true;
+        }
+    }
+    false
+}
@@ -1,7 +1,19 @@
+pub mod bot;
 pub mod camera;
+pub mod detector;
+pub mod directional;
+pub mod flick;
 pub mod game;
+pub mod ghost;
+pub mod headless;
+pub mod motion;
+pub mod reaction;
+pub mod replay;
+pub mod staircase;
+pub mod stats;
 pub mod target;
 pub mod utils;
+pub mod xcorr;
 
 use camera::*;
 use game::*;
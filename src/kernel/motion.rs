@@ -0,0 +1,419 @@
+use crate::state::*;
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f32::consts::PI;
+
+// За какое время jerk-limited модель стремится догнать желаемую скорость, секунды
+const JERK_RESPONSE_TIME: f32 = 0.1;
+
+// Положение камеры и ее оси на момент старта теста
+pub struct TargetFrame {
+    pub cam_pos: Vec3,
+    pub forward: Vec3,
+    pub right: Vec3,
+    pub up: Vec3,
+}
+
+impl TargetFrame {
+    // Позиция относительно камеры в осях кадра: (вправо, вверх, вперед)
+    fn local(&self, position: Vec3) -> Vec3 {
+        let rel = position - self.cam_pos;
+        Vec3::new(rel.dot(self.right), rel.dot(self.up), rel.dot(self.forward))
+    }
+
+    fn world(&self, local: Vec3) -> Vec3 {
+        self.cam_pos + self.right * local.x + self.up * local.y + self.forward * local.z
+    }
+}
+
+// Все, что нужно модели на одном тике
+pub struct MotionContext<'a> {
+    pub rng: &'a mut StdRng,
+    pub scenario: &'a Scenario,
+    pub frame: &'a TargetFrame,
+    pub time: f32,
+    pub dt: f32,
+    // Скорость таргета с учетом лестницы; сценарий хранит исходную
+    pub speed: f32,
+    // Лестница по интервалам делит на это число интервалы смены направления
+    pub interval_scale: f32,
+}
+
+impl MotionContext<'_> {
+    // Случайный интервал до следующей смены направления
+    fn change_interval(&mut self, min: f32, max: f32) -> f32 {
+        self.rng
+            .gen_range(min / self.interval_scale..=max / self.interval_scale)
+    }
+}
+
+// Модель движения таргета. init вызывается один раз при создании таргета,
+// step - на каждом тике FixedUpdate
+pub trait MotionModel {
+    fn init(&self, _target: &mut TargetState, _ctx: &mut MotionContext) {}
+    fn step(&self, target: &mut TargetState, ctx: &mut MotionContext);
+}
+
+// Модель по конфигу сценария
+pub fn motion_model(config: &MotionConfig) -> &dyn MotionModel {
+    match config {
+        MotionConfig::RandomTurn => &RandomTurnMotion,
+        MotionConfig::Sinusoidal(motion) => motion,
+        MotionConfig::Adad(motion) => motion,
+        MotionConfig::JerkLimited(motion) => motion,
+    }
+}
+
+// Прижимает позицию к границам сценария; true, если она была снаружи
+fn clamp_to_bounds(position: Vec3, scenario: &Scenario, frame: &TargetFrame) -> (Vec3, bool) {
+    let local = frame.local(position);
+    let clamped = Vec3::new(
+        local
+            .x
+            .clamp(-scenario.target_bound_x, scenario.target_bound_x),
+        local
+            .y
+            .clamp(-scenario.target_bound_y, scenario.target_bound_y),
+        local
+            .z
+            .clamp(scenario.target_bound_z_min, scenario.target_bound_z_max),
+    );
+    (frame.world(clamped), clamped != local)
+}
+
+// Качание дистанции поверх любой модели: глубину задает время, от дальней границы
+// к ближней и обратно
+pub fn apply_distance_sweep(target: &mut TargetState, ctx: &MotionContext) {
+    let period = ctx.scenario.distance_sweep_period;
+    if period <= 0.0 {
+        return;
+    }
+    let (z_min, z_max) = (
+        ctx.scenario.target_bound_z_min,
+        ctx.scenario.target_bound_z_max,
+    );
+    let progress = 0.5 - 0.5 * (2.0 * PI * ctx.time / period).cos();
+    let mut local = ctx.frame.local(target.position);
+    local.z = z_max - (z_max - z_min) * progress;
+    target.position = ctx.frame.world(local);
+}
+
+// Исходная модель: постоянная скорость и резкий поворот по таймеру или у границы
+pub struct RandomTurnMotion;
+
+impl MotionModel for RandomTurnMotion {
+    fn step(&self, target: &mut TargetState, ctx: &mut MotionContext) {
+        let scenario = ctx.scenario;
+        let (cam_pos, f, r, up) = (
+            ctx.frame.cam_pos,
+            ctx.frame.forward,
+            ctx.frame.right,
+            ctx.frame.up,
+        );
+        let (current_time, dt) = (ctx.time, ctx.dt);
+
+        // Проверяем, нужно ли изменить направление по времени
+        let should_change_direction = current_time >= target.next_direction_change;
+
+        // Вычисляем новую позицию
+        let mut new_pos = target.position + target.velocity * dt;
+
+        // Проверяем столкновение с границами
+        let rel = new_pos - cam_pos;
+        let x = rel.dot(r);
+        let y = rel.dot(up);
+        let z = rel.dot(f);
+
+        let bound_x = scenario.target_bound_x;
+        let bound_y = scenario.target_bound_y;
+        let bound_z_min = scenario.target_bound_z_min;
+        let bound_z_max = scenario.target_bound_z_max;
+
+        let hit_boundary = x < -bound_x
+            || x > bound_x
+            || y < -bound_y
+            || y > bound_y
+            || z < bound_z_min
+            || z > bound_z_max;
+
+        // Изменяем направление если нужно
+        if should_change_direction || hit_boundary {
+            // Получаем текущее направление в 2D (игнорируем Z)
+            let current_dir_2d = Vec3::new(target.velocity.x, target.velocity.y, 0.0);
+            let current_angle = if current_dir_2d.length() > 0.01 {
+                current_dir_2d.y.atan2(current_dir_2d.x)
+            } else {
+                0.0
+            };
+
+            // Генерируем новый угол с минимальной разницей из сценария
+            let min_angle_diff = scenario.min_turn_angle.to_radians();
+            let angle_range = 2.0 * PI - 2.0 * min_angle_diff; // Доступный диапазон углов
+
+            let random_offset = ctx.rng.gen_range(0.0..angle_range);
+            let new_angle = current_angle + min_angle_diff + random_offset;
+
+            // Создаем новое направление
+            target.velocity = Vec3::new(new_angle.cos(), new_angle.sin(), 0.0) * ctx.speed;
+
+            // Движение по глубине; без depth_speed генератор не трогаем, пути по seed те же
+            if scenario.depth_speed > 0.0 {
+                target.velocity += f * ctx
+                    .rng
+                    .gen_range(-scenario.depth_speed..=scenario.depth_speed);
+            }
+
+            // Если столкнулись с границей, убеждаемся что движемся от неё
+            if hit_boundary {
+                // Получаем направление от границы к центру
+                let to_center = -rel.normalize_or_zero();
+
+                // Проецируем новое направление, чтобы оно не вело к границе
+                let dot = target.velocity.dot(to_center);
+                if dot < 0.0 {
+                    // Если направление ведет к границе, отражаем его
+                    target.velocity -= 2.0 * dot * to_center;
+                }
+
+                // По глубине разворачиваем внутрь слоя
+                if scenario.depth_speed > 0.0 {
+                    let vz = target.velocity.dot(f);
+                    if (z < bound_z_min && vz < 0.0) || (z > bound_z_max && vz > 0.0) {
+                        target.velocity -= 2.0 * vz * f;
+                    }
+                }
+            }
+
+            // Устанавливаем следующее время изменения направления
+            target.change_interval =
+                ctx.change_interval(scenario.change_interval_min, scenario.change_interval_max);
+            target.next_direction_change = current_time + target.change_interval;
+            target.last_direction_change_time = current_time;
+        }
+
+        // Обновляем позицию без сглаживания
+        new_pos = target.position + target.velocity * dt;
+
+        // Применяем ограничения границ
+        let rel = new_pos - cam_pos;
+        let x = rel.dot(r).clamp(-bound_x, bound_x);
+        let y = rel.dot(up).clamp(-bound_y, bound_y);
+        let z = rel.dot(f).clamp(bound_z_min, bound_z_max);
+
+        target.position = cam_pos + r * x + up * y + f * z;
+    }
+}
+
+// Смещение синусоиды от центра в осях кадра
+fn sinusoidal_offset(motion: &SinusoidalMotion, time: f32, phase: f32) -> Vec2 {
+    let wx = 2.0 * PI / motion.period_x;
+    let wy = 2.0 * PI / motion.period_y;
+    Vec2::new(
+        motion.amplitude_x * (wx * time + phase).sin(),
+        motion.amplitude_y * (wy * time + phase).sin(),
+    )
+}
+
+impl MotionModel for SinusoidalMotion {
+    fn init(&self, target: &mut TargetState, ctx: &mut MotionContext) {
+        // Случайная фаза, центр выбран так, чтобы таргет стартовал с текущей точки
+        target.phase = ctx.rng.gen_range(0.0..2.0 * PI);
+        let offset = sinusoidal_offset(self, 0.0, target.phase);
+        target.origin = target.position - ctx.frame.right * offset.x - ctx.frame.up * offset.y;
+    }
+
+    fn step(&self, target: &mut TargetState, ctx: &mut MotionContext) {
+        let offset = sinusoidal_offset(self, ctx.time, target.phase);
+        let position = target.origin + ctx.frame.right * offset.x + ctx.frame.up * offset.y;
+        let (position, _) = clamp_to_bounds(position, ctx.scenario, ctx.frame);
+
+        target.velocity = (position - target.position) / ctx.dt;
+        target.position = position;
+    }
+}
+
+impl MotionModel for AdadMotion {
+    fn init(&self, target: &mut TargetState, ctx: &mut MotionContext) {
+        let side = if ctx.rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+        target.velocity = Vec3::ZERO;
+        target.desired_velocity = ctx.frame.right * side * ctx.speed;
+        target.change_interval = ctx.rng.gen_range(self.strafe_min..=self.strafe_max);
+        target.next_direction_change = target.change_interval;
+    }
+
+    fn step(&self, target: &mut TargetState, ctx: &mut MotionContext) {
+        let frame = ctx.frame;
+        let x = frame.local(target.position).x;
+        let side = target.desired_velocity.dot(frame.right).signum();
+        let at_boundary = (side > 0.0 && x >= ctx.scenario.target_bound_x)
+            || (side < 0.0 && x <= -ctx.scenario.target_bound_x);
+
+        // Смена стороны: по таймеру или у границы, если стрейф ведет за нее
+        if ctx.time >= target.next_direction_change || at_boundary {
+            target.desired_velocity = frame.right * -side * ctx.speed;
+            target.change_interval = ctx.rng.gen_range(self.strafe_min..=self.strafe_max);
+            target.next_direction_change = ctx.time + target.change_interval;
+            target.last_direction_change_time = ctx.time;
+        }
+
+        // Разгон и торможение с постоянным ускорением
+        let dv = target.desired_velocity - target.velocity;
+        target.velocity += dv.clamp_length_max(self.acceleration * ctx.dt);
+
+        let (position, _) = clamp_to_bounds(
+            target.position + target.velocity * ctx.dt,
+            ctx.scenario,
+            frame,
+        );
+        target.position = position;
+    }
+}
+
+// Новая желаемая скорость в плоскости кадра; у границы - не наружу
+fn pick_desired_velocity(target: &mut TargetState, ctx: &mut MotionContext, away_from: Vec3) {
+    let angle = ctx.rng.gen_range(0.0..2.0 * PI);
+    let mut direction = ctx.frame.right * angle.cos() + ctx.frame.up * angle.sin();
+    let to_center = -away_from.normalize_or_zero();
+    let dot = direction.dot(to_center);
+    if dot < 0.0 {
+        direction -= 2.0 * dot * to_center;
+    }
+    target.desired_velocity = direction * ctx.speed;
+
+    let depth_speed = ctx.scenario.depth_speed;
+    if depth_speed > 0.0 {
+        let mut vz = ctx.rng.gen_range(-depth_speed..=depth_speed);
+        if vz * away_from.dot(ctx.frame.forward) > 0.0 {
+            vz = -vz;
+        }
+        target.desired_velocity += ctx.frame.forward * vz;
+    }
+}
+
+impl MotionModel for JerkLimitedMotion {
+    fn init(&self, target: &mut TargetState, ctx: &mut MotionContext) {
+        target.acceleration = Vec3::ZERO;
+        target.desired_velocity = target.velocity;
+        target.change_interval =
+            ctx.change_interval(self.change_interval_min, self.change_interval_max);
+        target.next_direction_change = target.change_interval;
+    }
+
+    fn step(&self, target: &mut TargetState, ctx: &mut MotionContext) {
+        let (position, outside) = clamp_to_bounds(
+            target.position + target.velocity * ctx.dt,
+            ctx.scenario,
+            ctx.frame,
+        );
+
+        if ctx.time >= target.next_direction_change || outside {
+            let rel = if outside {
+                // Направление от центра границ к таргету
+                let local = ctx.frame.local(target.position);
+                let center_z =
+                    (ctx.scenario.target_bound_z_min + ctx.scenario.target_bound_z_max) / 2.0;
+                ctx.frame.right * local.x
+                    + ctx.frame.up * local.y
+                    + ctx.frame.forward * (local.z - center_z)
+            } else {
+                Vec3::ZERO
+            };
+            pick_desired_velocity(target, ctx, rel);
+            target.change_interval =
+                ctx.change_interval(self.change_interval_min, self.change_interval_max);
+            target.next_direction_change = ctx.time + target.change_interval;
+            target.last_direction_change_time = ctx.time;
+        }
+
+        // Ускорение тянет скорость к желаемой, но и оно, и рывок ограничены
+        let wanted = ((target.desired_velocity - target.velocity) / JERK_RESPONSE_TIME)
+            .clamp_length_max(self.max_acceleration);
+        target.acceleration +=
+            (wanted - target.acceleration).clamp_length_max(self.max_jerk * ctx.dt);
+        target.velocity += target.acceleration * ctx.dt;
+        target.position = position;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    const BOUNDS_EPSILON: f32 = 1e-4;
+
+    // Каждую модель гоняем 20 с на 1 кГц от центра зоны, с движением по глубине
+    fn assert_stays_inside(motion: MotionConfig) {
+        let scenario = Scenario {
+            motion,
+            depth_speed: 2.0,
+            ..default()
+        };
+        let frame = TargetFrame {
+            cam_pos: Vec3::ZERO,
+            forward: Vec3::NEG_Z,
+            right: Vec3::X,
+            up: Vec3::Y,
+        };
+        let depth = (scenario.target_bound_z_min + scenario.target_bound_z_max) / 2.0;
+        let start = frame.world(Vec3::new(0.0, 0.0, depth));
+        let mut target = TargetState::new(start, Vec3::X * scenario.target_speed);
+        let mut rng = StdRng::seed_from_u64(11);
+        let model = motion_model(&scenario.motion);
+        let mut ctx = MotionContext {
+            rng: &mut rng,
+            scenario: &scenario,
+            frame: &frame,
+            time: 0.0,
+            dt: 0.001,
+            speed: scenario.target_speed,
+            interval_scale: 1.0,
+        };
+        model.init(&mut target, &mut ctx);
+
+        let mut farthest: f32 = 0.0;
+        for tick in 1..=20_000 {
+            ctx.time = tick as f32 * ctx.dt;
+            model.step(&mut target, &mut ctx);
+            let local = frame.local(target.position);
+            assert!(
+                local.x.abs() <= scenario.target_bound_x + BOUNDS_EPSILON
+                    && local.y.abs() <= scenario.target_bound_y + BOUNDS_EPSILON
+                    && local.z >= scenario.target_bound_z_min - BOUNDS_EPSILON
+                    && local.z <= scenario.target_bound_z_max + BOUNDS_EPSILON,
+                "{:?} left the bounds at tick {}: {}",
+                scenario.motion,
+                tick,
+                local
+            );
+            farthest = farthest.max(local.x.abs());
+        }
+        // Модель не застряла в центре
+        assert!(farthest > 1.0, "{:?} barely moved", scenario.motion);
+    }
+
+    #[test]
+    fn random_turn_stays_inside_bounds() {
+        assert_stays_inside(MotionConfig::RandomTurn);
+    }
+
+    #[test]
+    fn sinusoidal_stays_inside_bounds() {
+        assert_stays_inside(MotionConfig::Sinusoidal(SinusoidalMotion {
+            // Амплитуда шире зоны: синусоиду прижимают границы
+            amplitude_x: 20.0,
+            ..default()
+        }));
+    }
+
+    #[test]
+    fn adad_stays_inside_bounds() {
+        assert_stays_inside(MotionConfig::Adad(AdadMotion::default()));
+    }
+
+    #[test]
+    fn jerk_limited_stays_inside_bounds() {
+        assert_stays_inside(MotionConfig::JerkLimited(JerkLimitedMotion::default()));
+    }
+}
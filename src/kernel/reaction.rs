@@ -0,0 +1,274 @@
+use crate::detector::DetectorRegistry;
+use crate::state::*;
+use crate::stats;
+use crate::target::{finish_test, record_data_point, test_time};
+use bevy::audio::{AudioPlayer, AudioSink, PlaybackSettings};
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use rand::Rng;
+use std::time::Instant;
+
+// Без клика попытка завершается через это время после стимула, секунды
+const REACTION_TIMEOUT: f32 = 2.0;
+// Ответ быстрее этого - упреждение, считаем фальстартом, секунды
+const REACTION_MIN_VALID: f32 = 0.1;
+// Сколько ждем клика на no-go стимул, прежде чем засчитать верный пропуск, секунды
+const REACTION_NOGO_WINDOW: f32 = 1.0;
+
+// Режимы реакции: таргет перед прицелом загорается после случайной паузы.
+// Простая реакция - любой клик, выбор - кнопка по цвету, go/no-go - клик только на цвет таргета
+#[allow(clippy::too_many_arguments)]
+pub fn update_reaction(
+    mut test: ResMut<ReactionTest>,
+    mut target_query: Query<&mut Transform, (With<Target>, Without<PlayerCamera>)>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    time_fixed: Res<Time<Fixed>>,
+    mut mouse_events: EventReader<MouseButtonInput>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+    game_audio: Res<GameAudio>,
+    mut finished_events: EventWriter<TestFinishedEvent>,
+    detectors: Res<DetectorRegistry>,
+) {
+    // Клики читаем всегда, чтобы старые не сработали в следующем тесте
+    let mode = test.scenario.mode;
+    let pressed = mouse_events
+        .read()
+        .filter(|e| e.state == ButtonState::Pressed)
+        .map(|e| e.button)
+        .find(|&button| match mode {
+            TestMode::Choice => button == MouseButton::Left || button == MouseButton::Right,
+            _ => button == settings.key_shoot,
+        });
+
+    if !test.is_running || !mode.is_reaction() {
+        return;
+    }
+
+    let Some(current_time) = test_time(&test, &time_fixed) else {
+        return;
+    };
+    if current_time >= test.scenario.test_duration {
+        if !test.test_completed {
+            finish_test(&mut test, &detectors);
+            finished_events.write(TestFinishedEvent);
+        }
+        return;
+    }
+
+    let cam_pos = camera_query
+        .single()
+        .map(|t| t.translation)
+        .unwrap_or(test.start_cam_pos);
+
+    if test.reaction_trial.is_none() {
+        start_reaction_trial(&mut test, current_time);
+    }
+    let Some(mut trial) = test.reaction_trial.take() else {
+        return;
+    };
+    let onset = !trial.stimulus_shown && current_time >= trial.stimulus_time();
+    trial.stimulus_shown = current_time >= trial.stimulus_time();
+    if onset && trial.auditory && !game_audio.stimulus_sounds.is_empty() {
+        // Звуки по очереди: выбор не трогает генератор, пути по seed не зависят от конфига
+        let sounds = &game_audio.stimulus_sounds;
+        let sound = sounds[test.reaction_trials.len() % sounds.len()].clone();
+        commands.spawn((
+            AudioPlayer::new(sound),
+            PlaybackSettings::DESPAWN,
+            StimulusSound {
+                trial_start: trial.start_time,
+                spawned: Instant::now(),
+            },
+        ));
+    }
+
+    let since_stimulus = current_time - trial.stimulus_time();
+    // На no-go стимул ответ не нужен
+    let needs_response = mode != TestMode::GoNoGo || trial.stimulus == 0;
+    let window = if needs_response {
+        REACTION_TIMEOUT
+    } else {
+        REACTION_NOGO_WINDOW
+    };
+    let mut result = ReactionTrial {
+        start_time: trial.start_time,
+        foreperiod: trial.foreperiod,
+        reaction_time: None,
+        false_start: false,
+        stimulus: trial.stimulus,
+        commission: false,
+        omission: false,
+        auditory: trial.auditory,
+        sound_start_delay: trial.sound_start_delay,
+    };
+    let finished = if let Some(button) = pressed {
+        if since_stimulus < REACTION_MIN_VALID {
+            result.false_start = true;
+        } else {
+            let correct = match mode {
+                TestMode::Choice => {
+                    let expected = if trial.stimulus == 0 {
+                        MouseButton::Left
+                    } else {
+                        MouseButton::Right
+                    };
+                    button == expected
+                }
+                _ => needs_response,
+            };
+            if correct {
+                result.reaction_time = Some(since_stimulus);
+                commands.spawn((
+                    AudioPlayer::new(game_audio.hit_sound.clone()),
+                    PlaybackSettings::DESPAWN,
+                ));
+            } else {
+                result.commission = true;
+            }
+        }
+        Some(result)
+    } else if since_stimulus >= window {
+        result.omission = needs_response;
+        Some(result)
+    } else {
+        None
+    };
+
+    record_data_point(&mut test, cam_pos, current_time);
+
+    match finished {
+        Some(result) => {
+            test.reaction_trials.push(result);
+            // Фальстарты не засчитываются в число попыток
+            let completed = test
+                .reaction_trials
+                .iter()
+                .filter(|t| !t.false_start)
+                .count();
+            if completed >= test.scenario.reaction_trials as usize {
+                finish_test(&mut test, &detectors);
+                finished_events.write(TestFinishedEvent);
+                return;
+            }
+            start_reaction_trial(&mut test, current_time);
+        }
+        None => test.reaction_trial = Some(trial),
+    }
+
+    if let Ok(mut target_transform) = target_query.single_mut() {
+        target_transform.translation = test.target_position;
+    }
+}
+
+// Новая попытка со случайной паузой до стимула
+fn start_reaction_trial(test: &mut ReactionTest, current_time: f32) {
+    let foreperiod = test
+        .rng
+        .gen_range(test.scenario.foreperiod_min..=test.scenario.foreperiod_max);
+    // Цвет стимула; в простой реакции генератор не трогаем
+    let stimulus = match test.scenario.mode {
+        TestMode::Choice => test.rng.gen_range(0..2),
+        TestMode::GoNoGo => {
+            if test.rng.gen_bool(test.scenario.go_probability as f64) {
+                0
+            } else {
+                test.rng.gen_range(1..REACTION_STIMULUS_COLORS)
+            }
+        }
+        _ => 0,
+    };
+    let auditory = match test.scenario.stimulus {
+        StimulusKind::Visual => false,
+        StimulusKind::Auditory => true,
+        StimulusKind::Mixed => test.rng.gen_bool(0.5),
+    };
+    test.reaction_trial = Some(ReactionTrialState {
+        start_time: current_time,
+        foreperiod,
+        stimulus_shown: false,
+        stimulus,
+        auditory,
+        sound_start_delay: None,
+    });
+}
+
+// Задержка старта звука: от тика стимула до появления AudioSink - планирование ECS,
+// декодирование и создание sink. Это не задержка вывода: буфер и устройство
+// отсюда не видны, их задержку игрок задает сам (audio_output_latency)
+pub fn measure_sound_start_delay(
+    mut test: ResMut<ReactionTest>,
+    sounds: Query<&StimulusSound, Added<AudioSink>>,
+) {
+    for sound in sounds.iter() {
+        let delay = sound.spawned.elapsed().as_secs_f32();
+        let start = sound.trial_start;
+        if let Some(trial) = test
+            .reaction_trial
+            .as_mut()
+            .filter(|t| t.start_time == start)
+        {
+            trial.sound_start_delay = Some(delay);
+        } else if let Some(trial) = test
+            .reaction_trials
+            .iter_mut()
+            .rev()
+            .find(|t| t.start_time == start)
+        {
+            trial.sound_start_delay = Some(delay);
+        }
+    }
+}
+
+pub fn analyze_reaction_trials(test: &mut ReactionTest) {
+    let times: Vec<f32> = test
+        .reaction_trials
+        .iter()
+        .filter_map(|t| t.reaction_time)
+        .collect();
+    test.reaction_summary = ReactionTrialSummary {
+        trials: test
+            .reaction_trials
+            .iter()
+            .filter(|t| !t.false_start)
+            .count(),
+        responses: times.len(),
+        false_starts: test
+            .reaction_trials
+            .iter()
+            .filter(|t| t.false_start)
+            .count(),
+        commission_errors: test.reaction_trials.iter().filter(|t| t.commission).count(),
+        omission_errors: test.reaction_trials.iter().filter(|t| t.omission).count(),
+        mean: stats::mean(&times).unwrap_or(0.0) * 1000.0,
+        median: stats::median(&times).unwrap_or(0.0) * 1000.0,
+        sd: stats::std_dev(&times).unwrap_or(0.0) * 1000.0,
+        ..default()
+    };
+
+    // Слуховые и зрительные попытки отдельно, задержку звука можно вычесть
+    let times_for = |auditory: bool| -> Vec<f32> {
+        test.reaction_trials
+            .iter()
+            .filter(|t| t.auditory == auditory)
+            .filter_map(|t| t.reaction_time)
+            .collect()
+    };
+    let auditory_times = times_for(true);
+    let start_delays: Vec<f32> = test
+        .reaction_trials
+        .iter()
+        .filter_map(|t| t.sound_start_delay)
+        .collect();
+    let summary = &mut test.reaction_summary;
+    summary.visual_mean = stats::mean(&times_for(false)).unwrap_or(0.0) * 1000.0;
+    summary.auditory_mean = stats::mean(&auditory_times).unwrap_or(0.0) * 1000.0;
+    if test.reaction_trials.iter().any(|t| t.auditory) {
+        summary.sound_start_delay = stats::mean(&start_delays).unwrap_or(0.0) * 1000.0;
+        summary.audio_latency = summary.sound_start_delay + test.run_settings.audio_output_latency;
+    }
+    if !auditory_times.is_empty() {
+        summary.auditory_corrected_mean = summary.auditory_mean - summary.audio_latency;
+    }
+}
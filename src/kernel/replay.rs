@@ -0,0 +1,413 @@
+use crate::state::*;
+use crate::target::calculate_angular_error;
+use crate::user_interface::ui::UI_COLORS;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+// След в сцене: последние REPLAY_TRAIL секунд, по REPLAY_TRAIL_POINTS точек на путь
+const REPLAY_TRAIL: f32 = 1.0;
+const REPLAY_TRAIL_POINTS: usize = 50;
+// Сколько смен направления и реакций из окна следа отмечать в сцене
+const REPLAY_EVENT_MARKERS: usize = 8;
+// Перемотка стрелками, секунды
+const REPLAY_SEEK_STEP: f32 = 1.0;
+const REPLAY_MIN_SPEED: f32 = 0.125;
+const REPLAY_MAX_SPEED: f32 = 4.0;
+
+// Таргеты, которые просмотр показывает и прячет
+type ReplayTargets<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static TargetIndex,
+        &'static mut Transform,
+        &'static mut Visibility,
+    ),
+    (With<Target>, Without<PlayerCamera>),
+>;
+
+// Точки следа и маркеры событий
+type ReplayMarkers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static ReplayMarker,
+        &'static mut Transform,
+        &'static mut Visibility,
+    ),
+    (Without<Target>, Without<PlayerCamera>),
+>;
+
+// Точка прицела на дистанции таргета: так след прицела лежит рядом со следом таргета
+fn crosshair_point(point: &DataPoint) -> Vec3 {
+    let distance = (point.target_pos - point.camera_pos).length();
+    point.camera_pos + point.crosshair_dir.normalize_or_zero() * distance
+}
+
+// Открывается с экрана результатов; на просмотре SPACE - пауза, стрелки - перемотка и скорость
+pub fn replay_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    test: Res<ReactionTest>,
+    mut viewer: ResMut<ReplayViewer>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !viewer.active {
+        return;
+    }
+
+    if keys.just_pressed(settings.key_settings) || keys.just_pressed(settings.key_replay) {
+        viewer.active = false;
+        // Запись только что пройденного теста - обратно к результатам
+        if test.test_completed {
+            next_state.set(AppState::Results);
+        }
+        return;
+    }
+    if keys.just_pressed(settings.key_start) {
+        // Запись кончилась - играем сначала
+        if !viewer.playing && viewer.time >= viewer.duration {
+            viewer.time = 0.0;
+        }
+        viewer.playing = !viewer.playing;
+    }
+    let duration = viewer.duration;
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        viewer.time = (viewer.time - REPLAY_SEEK_STEP).max(0.0);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        viewer.time = (viewer.time + REPLAY_SEEK_STEP).min(duration);
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        viewer.speed = (viewer.speed * 2.0).min(REPLAY_MAX_SPEED);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        viewer.speed = (viewer.speed / 2.0).max(REPLAY_MIN_SPEED);
+    }
+}
+
+// Перемотка: пока кнопка мыши зажата на таймлайне, время идет за курсором
+pub fn scrub_replay_timeline(
+    timeline_query: Query<(&Interaction, &RelativeCursorPosition), With<ReplayTimeline>>,
+    mut viewer: ResMut<ReplayViewer>,
+) {
+    for (interaction, cursor) in timeline_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Some(position) = cursor.normalized {
+            viewer.time = position.x.clamp(0.0, 1.0) * viewer.duration;
+        }
+    }
+}
+
+// Создает панель и точки следа при открытии просмотра и убирает их при закрытии
+#[allow(clippy::too_many_arguments)]
+pub fn sync_replay_scene(
+    mut commands: Commands,
+    viewer: Res<ReplayViewer>,
+    test: Res<ReactionTest>,
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    panel_query: Query<Entity, With<ReplayPanel>>,
+    marker_query: Query<Entity, With<ReplayMarker>>,
+    mut target_query: ReplayTargets,
+    mut camera_query: Query<&mut Transform, With<PlayerCamera>>,
+) {
+    let shown = !panel_query.is_empty();
+    if viewer.active && !shown {
+        spawn_replay_ui(&mut commands, &viewer, &settings, &asset_server);
+        spawn_replay_markers(&mut commands, &mut meshes, &mut materials);
+        // В записи есть только активный таргет
+        for (index, _, mut visibility) in target_query.iter_mut() {
+            if index.0 != 0 {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    } else if !viewer.active && shown {
+        for entity in panel_query.iter().chain(marker_query.iter()) {
+            commands.entity(entity).despawn();
+        }
+        // Камера и таргеты - как были после теста
+        for (index, mut transform, mut visibility) in target_query.iter_mut() {
+            transform.translation = test
+                .targets
+                .get(index.0)
+                .map_or(test.target_position, |t| t.position);
+            *visibility = Visibility::Inherited;
+        }
+        if let Ok(mut camera_transform) = camera_query.single_mut() {
+            camera_transform.rotation = Quat::from_axis_angle(Vec3::Y, test.camera_yaw)
+                * Quat::from_axis_angle(Vec3::X, test.camera_pitch);
+        }
+    }
+}
+
+fn spawn_replay_ui(
+    commands: &mut Commands,
+    viewer: &ReplayViewer,
+    settings: &Settings,
+    asset_server: &AssetServer,
+) {
+    let duration = viewer.duration.max(f32::EPSILON);
+    // Метка на таргетах: сверху смены направления, снизу реакции
+    let mark = |time: f32, top: bool, color: Color| {
+        (
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(time / duration * 100.0),
+                top: Val::Percent(if top { 0.0 } else { 50.0 }),
+                width: Val::Px(2.0),
+                height: Val::Percent(50.0),
+                ..default()
+            },
+            BackgroundColor(color),
+        )
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                right: Val::Px(20.0),
+                bottom: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(UI_COLORS.background),
+            BorderRadius::all(Val::Px(12.0)),
+            GameUI,
+            ReplayPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new(""),
+                TextFont {
+                    font: asset_server.load(&settings.font_file),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(UI_COLORS.text_primary),
+                ReplayInfoText,
+            ));
+            panel
+                .spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(28.0),
+                        ..default()
+                    },
+                    BackgroundColor(UI_COLORS.surface_light),
+                    BorderRadius::all(Val::Px(4.0)),
+                    Interaction::default(),
+                    RelativeCursorPosition::default(),
+                    ReplayTimeline,
+                ))
+                .with_children(|bar| {
+                    for change in &viewer.direction_changes {
+                        bar.spawn(mark(change.time, true, UI_COLORS.warning));
+                    }
+                    for reaction in &viewer.reactions {
+                        bar.spawn(mark(reaction.reaction_time, false, UI_COLORS.success));
+                    }
+                    bar.spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left: Val::Percent(0.0),
+                            width: Val::Px(2.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(UI_COLORS.text_primary),
+                        ReplayPlayhead,
+                    ));
+                });
+        });
+}
+
+fn spawn_replay_markers(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let mut material = |color: Color| {
+        materials.add(StandardMaterial {
+            base_color: color,
+            unlit: true,
+            ..default()
+        })
+    };
+    let target_trail = material(UI_COLORS.warning);
+    let crosshair_trail = material(UI_COLORS.danger);
+    let change = material(UI_COLORS.primary);
+    let reaction = material(UI_COLORS.success);
+    let trail_mesh = meshes.add(Sphere::new(0.04));
+    let event_mesh = meshes.add(Sphere::new(0.12));
+
+    let mut spawn =
+        |marker: ReplayMarker, mesh: &Handle<Mesh>, material: &Handle<StandardMaterial>| {
+            commands.spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::default(),
+                Visibility::Hidden,
+                marker,
+            ));
+        };
+    for slot in 0..REPLAY_TRAIL_POINTS {
+        spawn(ReplayMarker::TargetTrail(slot), &trail_mesh, &target_trail);
+        spawn(
+            ReplayMarker::CrosshairTrail(slot),
+            &trail_mesh,
+            &crosshair_trail,
+        );
+    }
+    for slot in 0..REPLAY_EVENT_MARKERS {
+        spawn(ReplayMarker::DirectionChange(slot), &event_mesh, &change);
+        spawn(ReplayMarker::Reaction(slot), &event_mesh, &reaction);
+    }
+}
+
+// Проигрывает запись: камера смотрит по crosshair_dir, таргет стоит в target_pos
+pub fn update_replay(
+    time: Res<Time>,
+    mut viewer: ResMut<ReplayViewer>,
+    mut camera_query: Query<&mut Transform, With<PlayerCamera>>,
+    mut target_query: TargetTransforms,
+    mut marker_query: ReplayMarkers,
+    mut playhead_query: Query<&mut Node, With<ReplayPlayhead>>,
+    mut text_query: Query<&mut Text, With<ReplayInfoText>>,
+) {
+    if !viewer.active || viewer.data.is_empty() {
+        return;
+    }
+    if viewer.playing {
+        viewer.time += time.delta_secs() * viewer.speed;
+        if viewer.time >= viewer.duration {
+            viewer.time = viewer.duration;
+            viewer.playing = false;
+        }
+    }
+
+    let now = viewer.time;
+    let point = viewer.data[viewer.index_at(now)].clone();
+    if let Ok(mut camera_transform) = camera_query.single_mut() {
+        camera_transform.translation = point.camera_pos;
+        camera_transform.look_to(point.crosshair_dir, Vec3::Y);
+    }
+    for (index, mut transform) in target_query.iter_mut() {
+        if index.0 == 0 {
+            transform.translation = point.target_pos;
+        }
+    }
+
+    // События из окна следа, последние первыми
+    let in_trail = |t: f32| t <= now && t >= now - REPLAY_TRAIL;
+    let changes: Vec<Vec3> = viewer
+        .direction_changes
+        .iter()
+        .rev()
+        .filter(|c| in_trail(c.time))
+        .map(|c| viewer.data[viewer.index_at(c.time)].target_pos)
+        .collect();
+    let reactions: Vec<Vec3> = viewer
+        .reactions
+        .iter()
+        .rev()
+        .filter(|r| in_trail(r.reaction_time))
+        .map(|r| crosshair_point(&viewer.data[viewer.index_at(r.reaction_time)]))
+        .collect();
+
+    // След начинается с последнего скачка трассы, а не тянется от прежнего таргета
+    let trail_start = viewer
+        .trace_jumps
+        .iter()
+        .copied()
+        .filter(|&jump| jump <= now)
+        .fold(0.0, f32::max);
+
+    for (marker, mut transform, mut visibility) in marker_query.iter_mut() {
+        let position = match *marker {
+            ReplayMarker::TargetTrail(slot) | ReplayMarker::CrosshairTrail(slot) => {
+                let t = now - REPLAY_TRAIL * slot as f32 / REPLAY_TRAIL_POINTS as f32;
+                (t >= trail_start).then(|| {
+                    let sample = &viewer.data[viewer.index_at(t)];
+                    match *marker {
+                        ReplayMarker::TargetTrail(_) => sample.target_pos,
+                        _ => crosshair_point(sample),
+                    }
+                })
+            }
+            ReplayMarker::DirectionChange(slot) => changes.get(slot).copied(),
+            ReplayMarker::Reaction(slot) => reactions.get(slot).copied(),
+        };
+        match position {
+            Some(position) => {
+                transform.translation = position;
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    if let Ok(mut node) = playhead_query.single_mut() {
+        node.left = Val::Percent(now / viewer.duration.max(f32::EPSILON) * 100.0);
+    }
+
+    if let Ok(mut text) = text_query.single_mut() {
+        // Последняя смена направления и нашел ли детектор реакцию на нее
+        let last_change = viewer
+            .direction_changes
+            .iter()
+            .rev()
+            .find(|c| c.time <= now);
+        let change_line = match last_change {
+            Some(change) => match viewer
+                .reactions
+                .iter()
+                .find(|r| (r.change_time - change.time).abs() < 1e-4)
+            {
+                Some(reaction) => format!(
+                    "Last change {:.2} s: reaction {:.0} ms",
+                    change.time,
+                    reaction.delay * 1000.0
+                ),
+                None => format!("Last change {:.2} s: no reaction matched", change.time),
+            },
+            None => "Last change: -".to_string(),
+        };
+        let changes_so_far = viewer
+            .direction_changes
+            .iter()
+            .filter(|c| c.time <= now)
+            .count();
+        let reactions_so_far = viewer
+            .reactions
+            .iter()
+            .filter(|r| r.change_time <= now)
+            .count();
+
+        text.0 = format!(
+            "REPLAY - {}
+{:.2} / {:.2} s   x{}   {}
+Angular error: {:.2}°
+{}   (matched {}/{})
+SPACE - play/pause, LEFT/RIGHT - {:.0} s, UP/DOWN - speed, drag the timeline to scrub, ESC - exit",
+            viewer.title,
+            now,
+            viewer.duration,
+            viewer.speed,
+            if viewer.playing { "PLAYING" } else { "PAUSED" },
+            calculate_angular_error(point.target_pos - point.camera_pos, point.crosshair_dir),
+            change_line,
+            reactions_so_far,
+            changes_so_far,
+            REPLAY_SEEK_STEP
+        );
+    }
+}
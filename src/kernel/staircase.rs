@@ -0,0 +1,181 @@
+use crate::state::*;
+use crate::stats;
+
+// Название и единицы параметра лестницы для результатов
+pub fn staircase_parameter_name(parameter: StaircaseParameter) -> (&'static str, &'static str) {
+    match parameter {
+        StaircaseParameter::Speed => ("speed", "m/s"),
+        StaircaseParameter::Size => ("size", "m"),
+        StaircaseParameter::ChangeInterval => ("change interval", "s"),
+    }
+}
+
+// Интервалы смены направления, которые меняет лестница: у jerk-limited они свои
+fn base_change_interval(scenario: &Scenario) -> (f32, f32) {
+    match &scenario.motion {
+        MotionConfig::JerkLimited(m) => (m.change_interval_min, m.change_interval_max),
+        _ => (scenario.change_interval_min, scenario.change_interval_max),
+    }
+}
+
+// Значение параметра на уровне сложности level (1 - как в сценарии, больше - сложнее)
+fn staircase_value(parameter: StaircaseParameter, scenario: &Scenario, level: f32) -> f32 {
+    match parameter {
+        StaircaseParameter::Speed => scenario.target_speed * level,
+        StaircaseParameter::Size => scenario.target_size / level,
+        StaircaseParameter::ChangeInterval => {
+            let (min, max) = base_change_interval(scenario);
+            (min + max) / 2.0 / level
+        }
+    }
+}
+
+// Уровень после блока: change 1 - сложнее, -1 - проще
+fn next_level(config: &StaircaseConfig, level: f32, change: i8) -> f32 {
+    let factor = 1.0 + config.step;
+    match change {
+        1 => level * factor,
+        -1 => level / factor,
+        _ => level,
+    }
+    .clamp(config.min_level, config.max_level)
+}
+
+// Текущий уровень, если лестница меняет этот параметр; иначе 1.
+// Сценарий теста не меняется, действующие значения считаются от уровня
+fn staircase_level(test: &ReactionTest, parameter: StaircaseParameter) -> f32 {
+    match (&test.scenario.staircase, &test.staircase) {
+        (Some(config), Some(state)) if config.parameter == parameter => state.level,
+        _ => 1.0,
+    }
+}
+
+// Текущее значение параметра лестницы для HUD
+pub fn staircase_current_value(test: &ReactionTest) -> Option<f32> {
+    let config = test.scenario.staircase.as_ref()?;
+    let level = staircase_level(test, config.parameter);
+    Some(staircase_value(config.parameter, &test.scenario, level))
+}
+
+pub fn staircase_target_speed(test: &ReactionTest) -> f32 {
+    test.scenario.target_speed * staircase_level(test, StaircaseParameter::Speed)
+}
+
+pub fn staircase_target_size(test: &ReactionTest) -> f32 {
+    test.scenario.target_size / staircase_level(test, StaircaseParameter::Size)
+}
+
+// Интервалы смены направления делятся на это число
+pub fn staircase_interval_scale(test: &ReactionTest) -> f32 {
+    staircase_level(test, StaircaseParameter::ChangeInterval)
+}
+
+// Во сколько раз таргет сейчас больше или меньше размера из сценария
+pub fn staircase_size_scale(test: &ReactionTest) -> f32 {
+    1.0 / staircase_level(test, StaircaseParameter::Size)
+}
+
+// Радиус таргета в момент time, восстановленный по записанным блокам лестницы.
+// Нужен анализу: к его запуску лестница уже сброшена или тест загружен из файла
+pub fn staircase_size_at(test: &ReactionTest, time: f32) -> f32 {
+    let Some(config) = &test.scenario.staircase else {
+        return test.scenario.target_size;
+    };
+    if config.parameter != StaircaseParameter::Size {
+        return test.scenario.target_size;
+    }
+    // Уровень меняется в конце блока и действует со следующего тика
+    let finished = test.staircase_steps.partition_point(|s| s.time < time);
+    let level = test.staircase_steps[..finished]
+        .iter()
+        .fold(1.0, |level, step| next_level(config, level, step.change));
+    test.scenario.target_size / level
+}
+
+// Каждый тик слежения: копит время на таргете, по окончании блока меняет сложность
+pub fn update_staircase(test: &mut ReactionTest, on_target: bool, current_time: f32) {
+    let Some(config) = test.scenario.staircase.clone() else {
+        return;
+    };
+
+    let state = test.staircase.get_or_insert(StaircaseState {
+        level: 1.0,
+        block_start: current_time,
+        ticks: 0,
+        on_target_ticks: 0,
+        successes: 0,
+        failures: 0,
+    });
+
+    state.ticks += 1;
+    if on_target {
+        state.on_target_ticks += 1;
+    }
+    if current_time - state.block_start < config.block_duration {
+        return;
+    }
+
+    let on_target = state.on_target_ticks as f32 / state.ticks as f32;
+    let value = staircase_value(config.parameter, &test.scenario, state.level);
+    let mut change = 0;
+    if on_target >= config.on_target_threshold {
+        state.successes += 1;
+        state.failures = 0;
+        if state.successes >= config.down {
+            state.successes = 0;
+            change = 1;
+        }
+    } else {
+        state.failures += 1;
+        state.successes = 0;
+        if state.failures >= config.up {
+            state.failures = 0;
+            change = -1;
+        }
+    }
+
+    state.level = next_level(&config, state.level, change);
+    state.block_start = current_time;
+    state.ticks = 0;
+    state.on_target_ticks = 0;
+
+    test.staircase_steps.push(StaircaseStep {
+        time: current_time,
+        value,
+        on_target,
+        change,
+    });
+}
+
+// Порог по последним разворотам
+pub fn analyze_staircase(test: &mut ReactionTest) {
+    let Some(config) = test.scenario.staircase.clone() else {
+        return;
+    };
+
+    // Разворот - смена сложности в сторону, противоположную предыдущей
+    let mut reversal_values = Vec::new();
+    let mut last_change = 0;
+    for step in test.staircase_steps.iter().filter(|s| s.change != 0) {
+        if last_change != 0 && step.change != last_change {
+            reversal_values.push(step.value);
+        }
+        last_change = step.change;
+    }
+
+    let start_value = staircase_value(config.parameter, &test.scenario, 1.0);
+    let final_value = test
+        .staircase_steps
+        .last()
+        .map_or(start_value, |step| step.value);
+    let recent = &reversal_values[reversal_values.len().saturating_sub(config.reversals)..];
+
+    test.staircase_summary = StaircaseSummary {
+        parameter: staircase_parameter_name(config.parameter).0.to_string(),
+        threshold: stats::mean(recent).unwrap_or(final_value),
+        reversals: reversal_values.len(),
+        blocks: test.staircase_steps.len(),
+        start_value,
+        final_value,
+    };
+}
@@ -0,0 +1,188 @@
+// Простые статистики для анализа результатов
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+pub fn mean(values: &[f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f32>() / values.len() as f32)
+}
+
+pub fn median(values: &[f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    })
+}
+
+// Доля part от total в процентах; без попыток 0, а не NaN
+pub fn percent(part: usize, total: usize) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    part as f32 / total as f32 * 100.0
+}
+
+// Выборочное стандартное отклонение
+pub fn std_dev(values: &[f32]) -> Option<f32> {
+    if values.len() < 2 {
+        return None;
+    }
+    let m = mean(values)?;
+    let sum: f32 = values.iter().map(|v| (v - m) * (v - m)).sum();
+    Some((sum / (values.len() - 1) as f32).sqrt())
+}
+
+// Процентиль с линейной интерполяцией между соседними значениями, p от 0 до 100
+pub fn percentile(values: &[f32], p: f32) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let rank = (p / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f32;
+    let low = rank.floor() as usize;
+    let high = rank.ceil() as usize;
+    Some(sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f32))
+}
+
+// Бутстреп-интервал для среднего (метод процентилей), confidence от 0 до 1.
+// Seed фиксирован, чтобы повторный анализ того же теста давал тот же интервал
+pub fn bootstrap_mean_ci(
+    values: &[f32],
+    resamples: usize,
+    confidence: f32,
+    seed: u64,
+) -> Option<(f32, f32)> {
+    if values.len() < 2 || resamples == 0 {
+        return None;
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let means: Vec<f32> = (0..resamples)
+        .map(|_| {
+            let sum: f32 = (0..values.len())
+                .map(|_| values[rng.gen_range(0..values.len())])
+                .sum();
+            sum / values.len() as f32
+        })
+        .collect();
+    let tail = (1.0 - confidence) / 2.0 * 100.0;
+    Some((percentile(&means, tail)?, percentile(&means, 100.0 - tail)?))
+}
+
+// Коэффициент корреляции Пирсона
+pub fn pearson(x: &[f32], y: &[f32]) -> Option<f32> {
+    let n = x.len().min(y.len());
+    if n < 2 {
+        return None;
+    }
+    let (mx, my) = (mean(&x[..n])?, mean(&y[..n])?);
+    let mut sxy = 0.0;
+    let mut sxx = 0.0;
+    let mut syy = 0.0;
+    for i in 0..n {
+        let (dx, dy) = (x[i] - mx, y[i] - my);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    let norm = (sxx * syy).sqrt();
+    (norm > f32::EPSILON).then(|| sxy / norm)
+}
+
+// Линейная регрессия y = a + b * x методом наименьших квадратов, возвращает (a, b)
+pub fn linear_fit(x: &[f32], y: &[f32]) -> Option<(f32, f32)> {
+    let n = x.len().min(y.len());
+    if n < 2 {
+        return None;
+    }
+    let (mx, my) = (mean(&x[..n])?, mean(&y[..n])?);
+    let mut sxy = 0.0;
+    let mut sxx = 0.0;
+    for i in 0..n {
+        sxy += (x[i] - mx) * (y[i] - my);
+        sxx += (x[i] - mx) * (x[i] - mx);
+    }
+    if sxx <= f32::EPSILON {
+        return None;
+    }
+    let slope = sxy / sxx;
+    Some((my - slope * mx, slope))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn percentile_bounds_and_median() {
+        let values = [30.0, 10.0, 50.0, 20.0, 40.0];
+        assert_eq!(percentile(&values, 0.0), Some(10.0));
+        assert_eq!(percentile(&values, 50.0), Some(30.0));
+        assert_eq!(percentile(&values, 100.0), Some(50.0));
+        // Вне 0..100 - по краям
+        assert_eq!(percentile(&values, -5.0), Some(10.0));
+        assert_eq!(percentile(&values, 150.0), Some(50.0));
+    }
+
+    #[test]
+    fn percentile_interpolates() {
+        let values = [10.0, 20.0, 30.0, 40.0];
+        // rank = 0.1 * 3 = 0.3
+        assert!(close(percentile(&values, 10.0).unwrap(), 13.0));
+        // rank = 1.5 - как медиана четной выборки
+        assert!(close(percentile(&values, 50.0).unwrap(), 25.0));
+        assert!(close(percentile(&values, 90.0).unwrap(), 37.0));
+        assert_eq!(percentile(&[7.0], 90.0), Some(7.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn std_dev_is_sample() {
+        // Сумма квадратов отклонений 32, делим на n - 1 = 7
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert!(close(std_dev(&values).unwrap(), (32.0f32 / 7.0).sqrt()));
+        assert_eq!(std_dev(&[1.0, 1.0, 1.0]), Some(0.0));
+        assert_eq!(std_dev(&[1.0]), None);
+    }
+
+    #[test]
+    fn bootstrap_ci_contains_mean_and_repeats() {
+        let values: Vec<f32> = (0..40).map(|i| 150.0 + ((i * 37) % 23) as f32).collect();
+        let mean = mean(&values).unwrap();
+        let (low, high) = bootstrap_mean_ci(&values, 2000, 0.95, 0x5eed).unwrap();
+        assert!(
+            low < mean && mean < high,
+            "{} not in {}..{}",
+            mean,
+            low,
+            high
+        );
+        assert!(high - low < 10.0);
+        assert_eq!(
+            bootstrap_mean_ci(&values, 2000, 0.95, 0x5eed),
+            Some((low, high))
+        );
+        // Уже доверительная вероятность - уже интервал
+        let (low_80, high_80) = bootstrap_mean_ci(&values, 2000, 0.8, 0x5eed).unwrap();
+        assert!(low <= low_80 && high_80 <= high);
+    }
+
+    #[test]
+    fn bootstrap_ci_needs_two_values() {
+        assert_eq!(bootstrap_mean_ci(&[120.0], 2000, 0.95, 1), None);
+        assert_eq!(bootstrap_mean_ci(&[120.0, 130.0], 0, 0.95, 1), None);
+    }
+}
@@ -1,30 +1,52 @@
+use crate::detector::{Detection, DetectorRegistry, ReactionDetector};
+use crate::directional::analyze_directions;
+use crate::flick::analyze_flick;
+use crate::motion::{apply_distance_sweep, motion_model, MotionContext, TargetFrame};
+use crate::reaction::analyze_reaction_trials;
+use crate::staircase::{
+    analyze_staircase, staircase_interval_scale, staircase_size_at, staircase_size_scale,
+    staircase_target_size, staircase_target_speed, update_staircase,
+};
 use crate::state::*;
+use crate::stats;
+use crate::xcorr::analyze_cross_correlation;
 use bevy::audio::{AudioPlayer, PlaybackSettings, Volume};
 use bevy::prelude::*;
+use rand::rngs::StdRng;
 use rand::Rng;
 use rayon::prelude::*;
 use std::f32::consts::PI;
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_target(
     mut test: ResMut<ReactionTest>,
-    mut target_query: Query<&mut Transform, (With<Target>, Without<PlayerCamera>)>,
+    mut target_query: TargetTransforms,
     camera_query: Query<&Transform, With<PlayerCamera>>,
     time_fixed: Res<Time<Fixed>>,
     mut commands: Commands,
     game_audio: Res<GameAudio>,
+    mut finished_events: EventWriter<TestFinishedEvent>,
+    detectors: Res<DetectorRegistry>,
 ) {
-    if !test.is_running {
+    if !test.is_running || test.scenario.mode != TestMode::Tracking {
         return;
     }
     // Check if we can find target
     if target_query.is_empty() {
         return;
     }
-    let current_time = time_fixed.elapsed().as_secs_f32() - test.start_time;
+    let Some(current_time) = test_time(&test, &time_fixed) else {
+        return;
+    };
     // Check if test should end
-    if current_time >= TEST_DURATION {
+    if current_time >= test.scenario.test_duration {
         if !test.test_completed {
-            finish_test(&mut test);
+            finish_test(&mut test, &detectors);
+            finished_events.write(TestFinishedEvent);
+            // Размер, измененный лестницей, возвращаем к исходному
+            for (_, mut target_transform) in target_query.iter_mut() {
+                target_transform.scale = Vec3::ONE;
+            }
         }
         return;
     }
@@ -37,7 +59,7 @@ pub fn update_target(
             // Проверяем пересечение с целью
             let target_sphere = Sphere {
                 center: test.target_position,
-                radius: TARGET_SIZE,
+                radius: staircase_target_size(&test),
             };
             if ray_sphere_intersection(ray_origin, ray_direction, target_sphere) {
                 test.hits += 1;
@@ -57,87 +79,66 @@ pub fn update_target(
         .map(|t| t.translation)
         .unwrap_or(test.start_cam_pos);
 
-    // Проверяем, нужно ли изменить направление по времени
-    let should_change_direction = current_time >= test.next_direction_change;
-
-    // Вычисляем новую позицию
-    let mut new_pos = test.target_position + test.target_velocity * dt;
-
     // Получаем локальную систему координат камеры
     let f = test.start_cam_forward.normalize();
     let world_up = Vec3::Y;
     let r = f.cross(world_up).normalize_or_zero();
     let up = r.cross(f).normalize_or_zero();
+    let frame = TargetFrame {
+        cam_pos,
+        forward: f,
+        right: r,
+        up,
+    };
 
-    // Проверяем столкновение с границами
-    let rel = new_pos - cam_pos;
-    let x = rel.dot(r);
-    let y = rel.dot(up);
-    let z = rel.dot(f);
-
-    let hit_boundary = x < -TARGET_BOUND_X
-        || x > TARGET_BOUND_X
-        || y < -TARGET_BOUND_Y
-        || y > TARGET_BOUND_Y
-        || z < TARGET_BOUND_Z_MIN
-        || z > TARGET_BOUND_Z_MAX;
-
-    // Изменяем направление если нужно
-    if should_change_direction || hit_boundary {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-
-        // Получаем текущее направление в 2D (игнорируем Z)
-        let current_dir_2d = Vec3::new(test.target_velocity.x, test.target_velocity.y, 0.0);
-        let current_angle = if current_dir_2d.length() > 0.01 {
-            current_dir_2d.y.atan2(current_dir_2d.x)
-        } else {
-            0.0
-        };
-
-        // Генерируем новый угол с минимальной разницей в 45 градусов
-        let min_angle_diff = PI / 4.0; // 45 градусов
-        let angle_range = 2.0 * PI - 2.0 * min_angle_diff; // Доступный диапазон углов
-
-        let random_offset = rng.gen_range(0.0..angle_range);
-        let new_angle = current_angle + min_angle_diff + random_offset;
-
-        // Создаем новое направление
-        test.target_velocity = Vec3::new(new_angle.cos(), new_angle.sin(), 0.0) * TARGET_SPEED;
-
-        // Если столкнулись с границей, убеждаемся что движемся от неё
-        if hit_boundary {
-            // Получаем направление от границы к центру
-            let to_center = -rel.normalize_or_zero();
+    // Таргеты создаются на первом тике, когда стартовая позиция уже известна
+    if test.targets.is_empty() {
+        init_targets(&mut test, &frame);
+    }
 
-            // Проецируем новое направление, чтобы оно не вело к границе
-            let dot = test.target_velocity.dot(to_center);
-            if dot < 0.0 {
-                // Если направление ведет к границе, отражаем его
-                test.target_velocity = test.target_velocity - 2.0 * dot * to_center;
-            }
+    {
+        let speed = staircase_target_speed(&test);
+        let interval_scale = staircase_interval_scale(&test);
+        let test = &mut *test;
+        let model = motion_model(&test.scenario.motion);
+        let mut ctx = MotionContext {
+            rng: &mut test.rng,
+            scenario: &test.scenario,
+            frame: &frame,
+            time: current_time,
+            dt,
+            speed,
+            interval_scale,
+        };
+        for target in test.targets.iter_mut() {
+            model.step(target, &mut ctx);
+            apply_distance_sweep(target, &ctx);
         }
-
-        // Устанавливаем следующее время изменения направления
-        test.change_interval = rng.gen_range(0.2..0.5);
-        test.next_direction_change = current_time + test.change_interval;
-        test.last_direction_change_time = current_time;
     }
 
-    // Обновляем позицию без сглаживания
-    new_pos = test.target_position + test.target_velocity * dt;
-
-    // Применяем ограничения границ
-    let rel = new_pos - cam_pos;
-    let x = rel.dot(r).clamp(-TARGET_BOUND_X, TARGET_BOUND_X);
-    let y = rel.dot(up).clamp(-TARGET_BOUND_Y, TARGET_BOUND_Y);
-    let z = rel.dot(f).clamp(TARGET_BOUND_Z_MIN, TARGET_BOUND_Z_MAX);
-
-    test.target_position = cam_pos + r * x + up * y + f * z;
-
-    // Update target mesh position
-    if let Ok(mut target_transform) = target_query.single_mut() {
-        target_transform.translation = test.target_position;
+    update_target_switch(&mut test, cam_pos, current_time);
+    update_target_health(&mut test, &frame, current_time, dt);
+    let on_target = ray_hits_target(
+        cam_pos,
+        test.crosshair_direction,
+        test.targets[test.active_target].position,
+        staircase_target_size(&test),
+    );
+    update_staircase(&mut test, on_target, current_time);
+    let (position, velocity) = {
+        let active = &test.targets[test.active_target];
+        (active.position, active.velocity)
+    };
+    test.target_position = position;
+    test.target_velocity = velocity;
+
+    // Update target mesh positions
+    let size_scale = staircase_size_scale(&test);
+    for (index, mut target_transform) in target_query.iter_mut() {
+        if let Some(target) = test.targets.get(index.0) {
+            target_transform.translation = target.position;
+            target_transform.scale = Vec3::splat(size_scale);
+        }
     }
 
     // Convert world position to screen-space coordinates for XY analysis
@@ -145,7 +146,11 @@ pub fn update_target(
         .get_single()
         .map(|t| t.translation)
         .unwrap_or(test.start_cam_pos);
+    record_data_point(&mut test, cam_pos, current_time);
+}
 
+// Записывает тик в телеметрию теста
+pub fn record_data_point(test: &mut ReactionTest, cam_pos: Vec3, current_time: f32) {
     // Project to camera's local coordinate system for 2D analysis
     let f = test.start_cam_forward.normalize();
     let r = f.cross(Vec3::Y).normalize_or_zero();
@@ -173,284 +178,434 @@ pub fn update_target(
     };
     test.data.push(data_point);
 }
-pub fn finish_test(test: &mut ReactionTest) {
-    test.is_running = false;
-    test.test_completed = true;
-    analyze_results(test);
-}
-// Optimized analysis
-pub fn analyze_results(test: &mut ReactionTest) {
-    if test.data.len() < 50 {
-        return;
+fn init_targets(test: &mut ReactionTest, frame: &TargetFrame) {
+    let speed = staircase_target_speed(test);
+    let interval_scale = staircase_interval_scale(test);
+
+    // Первый таргет стартует перед прицелом, как и в режиме с одним таргетом
+    test.targets
+        .push(TargetState::new(test.target_position, test.target_velocity));
+    test.active_target = 0;
+
+    // Остальные - в случайных точках внутри границ
+    for _ in 1..test.scenario.target_count {
+        let target = random_target_state(&mut test.rng, &test.scenario, frame, speed);
+        test.targets.push(target);
+    }
+    if test.scenario.target_count > 1 {
+        test.next_target_switch = test
+            .rng
+            .gen_range(test.scenario.switch_interval_min..=test.scenario.switch_interval_max);
     }
 
-    // Анализ задержки реакции
-    analyze_reaction_delay(test);
+    // Начальное состояние модели движения (random_turn ничего не меняет)
+    let test = &mut *test;
+    let model = motion_model(&test.scenario.motion);
+    let mut ctx = MotionContext {
+        rng: &mut test.rng,
+        scenario: &test.scenario,
+        frame,
+        time: 0.0,
+        dt: 0.0,
+        speed,
+        interval_scale,
+    };
+    for target in test.targets.iter_mut() {
+        target.health = test.scenario.target_health;
+        model.init(target, &mut ctx);
+    }
+}
 
-    // Расчет точности (среднее угловое отклонение)
-    test.rms_distance = calculate_average_angular_error_simple(test);
-    test.peak_angular_error = calculate_peak_angular_error_simple(test);
+// Таргет в случайной точке внутри границ со случайным направлением движения
+fn random_target_state(
+    rng: &mut StdRng,
+    scenario: &Scenario,
+    frame: &TargetFrame,
+    speed: f32,
+) -> TargetState {
+    let x = rng.gen_range(-scenario.target_bound_x..=scenario.target_bound_x);
+    let y = rng.gen_range(-scenario.target_bound_y..=scenario.target_bound_y);
+    let z = rng.gen_range(scenario.target_bound_z_min..=scenario.target_bound_z_max);
+    let angle = rng.gen_range(0.0..2.0 * PI);
+    TargetState::new(
+        frame.cam_pos + frame.right * x + frame.up * y + frame.forward * z,
+        Vec3::new(angle.cos(), angle.sin(), 0.0) * speed,
+    )
 }
 
-pub fn analyze_reaction_delay(test: &mut ReactionTest) {
-    if test.data.len() < 100 {
+// Урон активному таргету, пока прицел на нем; убитый таргет появляется в новом месте
+fn update_target_health(test: &mut ReactionTest, frame: &TargetFrame, current_time: f32, dt: f32) {
+    if test.scenario.target_health <= 0.0 {
         return;
     }
 
-    // 1. Находим все значимые смены направления таргета
-    let target_direction_changes = find_target_direction_changes(test);
-
-    // 2. Для каждой смены направления таргета находим соответствующую реакцию игрока
-    let reaction_delays = calculate_reaction_delays(test, &target_direction_changes);
+    let index = test.active_target;
+    if !ray_hits_target(
+        frame.cam_pos,
+        test.crosshair_direction,
+        test.targets[index].position,
+        staircase_target_size(test),
+    ) {
+        return;
+    }
 
-    // 3. Вычисляем среднюю задержку
-    if !reaction_delays.is_empty() {
-        test.average_delay =
-            reaction_delays.iter().sum::<f32>() / reaction_delays.len() as f32 * 1000.0;
+    let target = &mut test.targets[index];
+    let damage = (test.scenario.damage_per_second * dt).min(target.health);
+    target.health -= damage;
+    // Урон в разминку не в счет, но таргет от него умирает как обычно
+    if current_time >= test.scenario.warmup {
+        test.damage_dealt += damage;
+    }
+    if target.health > 0.0 {
+        return;
+    }
 
-        // Дополнительная статистика
-        let mut sorted_delays = reaction_delays.clone();
-        sorted_delays.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    test.kills.push(Kill {
+        time: current_time,
+        target: index,
+        time_to_kill: current_time - target.spawn_time,
+    });
+
+    let speed = staircase_target_speed(test);
+    let interval_scale = staircase_interval_scale(test);
+    let mut respawned = random_target_state(&mut test.rng, &test.scenario, frame, speed);
+    respawned.health = test.scenario.target_health;
+    respawned.spawn_time = current_time;
+    let mut ctx = MotionContext {
+        rng: &mut test.rng,
+        scenario: &test.scenario,
+        frame,
+        time: current_time,
+        dt,
+        speed,
+        interval_scale,
+    };
+    motion_model(&test.scenario.motion).init(&mut respawned, &mut ctx);
+    test.targets[index] = respawned;
+}
 
-        // Медианная задержка (более устойчивая к выбросам)
-        let median_delay = if sorted_delays.len() % 2 == 0 {
-            (sorted_delays[sorted_delays.len() / 2 - 1] + sorted_delays[sorted_delays.len() / 2])
-                / 2.0
+pub fn analyze_kills(test: &mut ReactionTest) {
+    let warmup = test.scenario.warmup;
+    let times: Vec<f32> = test
+        .kills
+        .iter()
+        .filter(|k| k.time >= warmup)
+        .map(|k| k.time_to_kill)
+        .collect();
+    let max_damage =
+        test.scenario.damage_per_second * (test.scenario.test_duration - warmup).max(0.0);
+    test.kill_summary = KillSummary {
+        kills: times.len(),
+        average_time_to_kill: stats::mean(&times).unwrap_or(0.0) * 1000.0,
+        median_time_to_kill: stats::median(&times).unwrap_or(0.0) * 1000.0,
+        damage_dealt: test.damage_dealt,
+        damage_efficiency: if max_damage > 0.0 {
+            test.damage_dealt / max_damage
         } else {
-            sorted_delays[sorted_delays.len() / 2]
-        };
-
-        test.median_delay = median_delay * 1000.0;
-        test.react_directions = reaction_delays.len();
-        test.count_directions = target_direction_changes.len();
-    }
+            0.0
+        },
+    };
 }
 
-fn find_target_direction_changes(test: &ReactionTest) -> Vec<DirectionChange> {
-    let mut changes = Vec::new();
-    let window_size = 7; // Размер окна для сглаживания
-    let min_angle_change = 35.0; // Минимальный угол поворота в градусах
-    let min_speed = 0.2; // Минимальная скорость для анализа
-
-    for i in window_size..(test.data.len() - window_size) {
-        // Вычисляем сглаженные направления до и после точки
-        let old_direction = calculate_smoothed_target_direction(test, i - window_size, i);
-        let new_direction = calculate_smoothed_target_direction(test, i, i + window_size);
+// Смена активного таргета и время, за которое прицел перешел на новый
+fn update_target_switch(test: &mut ReactionTest, cam_pos: Vec3, current_time: f32) {
+    if test.targets.len() < 2 {
+        return;
+    }
 
-        // Проверяем, есть ли значимое изменение
-        if old_direction.length() < min_speed || new_direction.length() < min_speed {
-            continue;
+    let on_active = ray_hits_target(
+        cam_pos,
+        test.crosshair_direction,
+        test.targets[test.active_target].position,
+        staircase_target_size(test),
+    );
+    if let Some(switch) = test.target_switches.last_mut() {
+        if switch.switch_time.is_none() && on_active {
+            switch.switch_time = Some(current_time - switch.time);
         }
+    }
 
-        let old_dir_normalized = old_direction.normalize();
-        let new_dir_normalized = new_direction.normalize();
-
-        // Вычисляем угол между направлениями
-        let dot_product = old_dir_normalized.dot(new_dir_normalized).clamp(-1.0, 1.0);
-        let angle_change = dot_product.acos() * 180.0 / PI;
-
-        if angle_change >= min_angle_change {
-            // Проверяем, что это не дубликат (слишком близко к предыдущему)
-            let is_duplicate = changes.iter().any(|change: &DirectionChange| {
-                (change.time - test.data[i].time).abs() < 0.08 // Меньше 80мс разница
-            });
-
-            if !is_duplicate {
-                changes.push(DirectionChange {
-                    time: test.data[i].time,
-                    //target_old_direction: Vec2::new(old_dir_normalized.x, old_dir_normalized.y),
-                    target_new_direction: Vec2::new(new_dir_normalized.x, new_dir_normalized.y),
-                    //is_significant: angle_change >= 90.0, // Очень резкие повороты
-                });
-            }
-        }
+    if current_time >= test.next_target_switch {
+        let from = test.active_target;
+        let offset = test.rng.gen_range(1..test.targets.len());
+        test.active_target = (from + offset) % test.targets.len();
+        test.target_switches.push(TargetSwitch {
+            time: current_time,
+            from,
+            to: test.active_target,
+            switch_time: None,
+        });
+        test.next_target_switch = current_time
+            + test
+                .rng
+                .gen_range(test.scenario.switch_interval_min..=test.scenario.switch_interval_max);
     }
+}
 
-    changes
+pub fn analyze_target_switches(test: &mut ReactionTest) {
+    let switches: Vec<&TargetSwitch> = test
+        .target_switches
+        .iter()
+        .filter(|s| s.time >= test.scenario.warmup)
+        .collect();
+    let times: Vec<f32> = switches.iter().filter_map(|s| s.switch_time).collect();
+    test.switch_summary = SwitchSummary {
+        switches: switches.len(),
+        acquired: times.len(),
+        average_switch_time: stats::mean(&times).unwrap_or(0.0) * 1000.0,
+        median_switch_time: stats::median(&times).unwrap_or(0.0) * 1000.0,
+    };
 }
 
-fn calculate_smoothed_target_direction(
-    test: &ReactionTest,
-    start_idx: usize,
-    end_idx: usize,
-) -> Vec2 {
-    if start_idx >= end_idx || end_idx >= test.data.len() {
-        return Vec2::ZERO;
+// Тики делятся на SIZE_BIN_COUNT равных отрезков угловых размеров таргета
+fn analyze_size_bins(test: &mut ReactionTest) {
+    // (угловой размер, угловая ошибка) в градусах и был ли прицел на таргете
+    let samples: Vec<(f32, f32, bool)> = test
+        .data
+        .iter()
+        .map(|point| {
+            // Радиус меняется, если лестница идет по размеру
+            let radius = staircase_size_at(test, point.time);
+            let rel = point.target_pos - point.camera_pos;
+            let half_size = (radius / rel.length().max(radius)).asin();
+            let error = rel.angle_between(point.crosshair_dir);
+            (
+                (2.0 * half_size).to_degrees(),
+                error.to_degrees(),
+                error <= half_size,
+            )
+        })
+        .collect();
+
+    let min_size = samples.iter().map(|s| s.0).fold(f32::MAX, f32::min);
+    let max_size = samples.iter().map(|s| s.0).fold(f32::MIN, f32::max);
+    test.size_bins.clear();
+    if samples.is_empty() || max_size - min_size < SIZE_BIN_MIN_RANGE {
+        return;
     }
 
-    let mut total_direction = Vec2::ZERO;
-    let mut count = 0;
-
-    // Усредняем направления движения в окне
-    for i in start_idx..(end_idx - 1) {
-        let dt = test.data[i + 1].time - test.data[i].time;
-        if dt > 0.001 {
-            let direction = Vec2::new(
-                test.data[i + 1].target_x - test.data[i].target_x,
-                test.data[i + 1].target_y - test.data[i].target_y,
-            ) / dt;
-
-            if direction.length() > 0.1 {
-                // Фильтруем очень медленные движения
-                total_direction += direction;
-                count += 1;
-            }
-        }
+    let width = (max_size - min_size) / SIZE_BIN_COUNT as f32;
+    let mut bins: Vec<Vec<(f32, bool)>> = vec![Vec::new(); SIZE_BIN_COUNT];
+    for &(size, error, on_target) in &samples {
+        let index = (((size - min_size) / width) as usize).min(SIZE_BIN_COUNT - 1);
+        bins[index].push((error, on_target));
     }
 
-    if count > 0 {
-        total_direction / count as f32
-    } else {
-        Vec2::ZERO
+    for (i, bin) in bins.iter().enumerate() {
+        if bin.is_empty() {
+            continue;
+        }
+        let errors: Vec<f32> = bin.iter().map(|b| b.0).collect();
+        let on_target = bin.iter().filter(|b| b.1).count();
+        test.size_bins.push(SizeBin {
+            min_size: min_size + width * i as f32,
+            max_size: min_size + width * (i + 1) as f32,
+            time_share: bin.len() as f32 / samples.len() as f32,
+            average_error: stats::mean(&errors).unwrap_or(0.0),
+            on_target: on_target as f32 / bin.len() as f32,
+        });
     }
 }
 
-fn calculate_reaction_delays(test: &ReactionTest, target_changes: &[DirectionChange]) -> Vec<f32> {
-    let mut delays = Vec::new();
-
-    for change in target_changes {
-        // Ищем реакцию игрока в разумном временном окне (50-500мс после смены направления таргета)
-        let search_start_time = change.time + 0.07; // 70мс минимум
-        let search_end_time = change.time + 0.8; // 800мс максимум
+// Время от начала теста; None, пока идет обратный отсчет или тест на паузе.
+// Считаем от Duration, чтобы тики не зависели от точности f32 на большом uptime
+pub fn test_time(test: &ReactionTest, time_fixed: &Time<Fixed>) -> Option<f32> {
+    if test.paused {
+        return None;
+    }
+    time_fixed
+        .elapsed()
+        .checked_sub(test.fixed_start)
+        .map(|elapsed| elapsed.as_secs_f32())
+}
 
-        if let Some(player_reaction_time) =
-            find_player_reaction(test, change, search_start_time, search_end_time)
-        {
-            let delay = player_reaction_time - change.time;
-            delays.push(delay);
+pub fn finish_test(test: &mut ReactionTest, detectors: &DetectorRegistry) {
+    test.is_running = false;
+    test.test_completed = true;
+    analyze_results(test, detectors);
+}
+// Optimized analysis
+pub fn analyze_results(test: &mut ReactionTest, detectors: &DetectorRegistry) {
+    match test.scenario.mode {
+        TestMode::Flick => {
+            analyze_flick(test);
+            return;
+        }
+        TestMode::Reaction | TestMode::Choice | TestMode::GoNoGo => {
+            analyze_reaction_trials(test);
+            return;
         }
+        TestMode::Tracking => {}
     }
 
-    delays
+    // Лестница меняла параметры сценария - возвращаем их до остального анализа
+    analyze_staircase(test);
+
+    // Разминка остается в телеметрии, но в анализ не идет
+    let warmup = test.scenario.warmup;
+    if warmup > 0.0 {
+        let analyzed = test
+            .data
+            .iter()
+            .filter(|point| point.time >= warmup)
+            .cloned()
+            .collect();
+        let recorded = std::mem::replace(&mut test.data, analyzed);
+        analyze_tracking(test, detectors);
+        test.data = recorded;
+    } else {
+        analyze_tracking(test, detectors);
+    }
 }
 
-fn find_player_reaction(
-    test: &ReactionTest,
-    target_change: &DirectionChange,
-    search_start_time: f32,
-    search_end_time: f32,
-) -> Option<f32> {
-    // Находим индексы для поиска
-    let start_idx = test.data.iter().position(|d| d.time >= search_start_time)?;
-    let end_idx = test.data.iter().rposition(|d| d.time <= search_end_time)?;
-
-    if start_idx >= end_idx {
-        return None;
+fn analyze_tracking(test: &mut ReactionTest, detectors: &DetectorRegistry) {
+    if test.data.len() < 50 {
+        return;
     }
 
-    let window_size = 3;
-    let min_angle_change = 25.0; // Минимальное изменение направления прицела
-
-    // Вычисляем направление движения прицела до момента смены направления таргета
-    let pre_change_crosshair_direction =
-        calculate_crosshair_direction_before_time(test, target_change.time)?;
-
-    // Ищем момент, когда игрок начал менять направление движения прицела
-    for i in start_idx..(end_idx - window_size) {
-        let current_crosshair_direction =
-            calculate_smoothed_crosshair_direction(test, i, i + window_size);
+    // Анализ задержки реакции
+    analyze_reaction_delay(test, detectors);
 
-        if current_crosshair_direction.length() < 0.1 {
-            continue;
-        }
+    // Альтернативная оценка: взаимная корреляция скоростей
+    analyze_cross_correlation(test);
 
-        let current_dir_normalized = current_crosshair_direction.normalize();
-        let old_dir_normalized = pre_change_crosshair_direction.normalize();
+    // Время переключения между таргетами
+    analyze_target_switches(test);
 
-        // Вычисляем угол изменения направления прицела
-        let dot_product = old_dir_normalized
-            .dot(current_dir_normalized)
-            .clamp(-1.0, 1.0);
-        let angle_change = dot_product.acos() * 180.0 / PI;
+    // Убийства и урон (режим со здоровьем)
+    if test.scenario.target_health > 0.0 {
+        analyze_kills(test);
+    }
 
-        if angle_change >= min_angle_change {
-            // Дополнительно проверяем, что новое направление более соответствует новому направлению таргета
-            let target_alignment = current_dir_normalized.dot(target_change.target_new_direction);
-            let old_target_alignment = old_dir_normalized.dot(target_change.target_new_direction);
+    // Точность в зависимости от углового размера таргета
+    analyze_size_bins(test);
 
-            if target_alignment > old_target_alignment + 0.05 {
-                // Улучшение соответствия
-                return Some(test.data[i].time);
-            }
-        }
-    }
+    // Реакции и точность по направлениям поворотов таргета
+    analyze_directions(test);
 
-    None
+    // Расчет точности (среднее угловое отклонение)
+    test.rms_distance = calculate_average_angular_error_simple(test);
+    test.peak_angular_error = calculate_peak_angular_error_simple(test);
 }
 
-// Вычисляет направление движения прицела до определенного момента времени
-fn calculate_crosshair_direction_before_time(test: &ReactionTest, time: f32) -> Option<Vec2> {
-    let time_window = 0.15; // 150мс до события
-    let start_time = time - time_window;
-
-    let relevant_points: Vec<_> = test
-        .data
+// Разброс задержек: одного среднего мало, чтобы понять, реальна ли разница в 10 мс
+pub fn delay_stats(detection: &Detection) -> DelayStats {
+    let delays: Vec<f32> = detection
+        .reactions
         .iter()
-        .filter(|d| d.time >= start_time && d.time < time)
+        .map(|r| r.delay * 1000.0)
         .collect();
-
-    if relevant_points.len() < 3 {
-        return None;
+    let (ci_low, ci_high) =
+        stats::bootstrap_mean_ci(&delays, BOOTSTRAP_RESAMPLES, 0.95, BOOTSTRAP_SEED)
+            .unwrap_or_default();
+    DelayStats {
+        samples: delays.len(),
+        std_dev: stats::std_dev(&delays).unwrap_or(0.0),
+        min: delays.iter().copied().reduce(f32::min).unwrap_or(0.0),
+        max: delays.iter().copied().reduce(f32::max).unwrap_or(0.0),
+        p10: stats::percentile(&delays, 10.0).unwrap_or(0.0),
+        p90: stats::percentile(&delays, 90.0).unwrap_or(0.0),
+        ci_low,
+        ci_high,
+        detection_rate: if detection.changes.is_empty() {
+            0.0
+        } else {
+            delays.len() as f32 / detection.changes.len() as f32
+        },
     }
+}
 
-    let mut total_direction = Vec2::ZERO;
-    let mut count = 0;
+// Моменты, когда трасса активного таргета скачком переходит в другое место:
+// смена таргета или появление убитого таргета в новой точке
+pub fn trace_jumps(switches: &[TargetSwitch], kills: &[Kill]) -> Vec<f32> {
+    let mut jumps: Vec<f32> = switches
+        .iter()
+        .map(|s| s.time)
+        .chain(kills.iter().map(|k| k.time))
+        .collect();
+    jumps.sort_by(f32::total_cmp);
+    jumps
+}
 
-    for i in 0..(relevant_points.len() - 1) {
-        let dt = relevant_points[i + 1].time - relevant_points[i].time;
-        if dt > 0.001 {
-            let direction = Vec2::new(
-                relevant_points[i + 1].crosshair_x - relevant_points[i].crosshair_x,
-                relevant_points[i + 1].crosshair_y - relevant_points[i].crosshair_y,
-            ) / dt;
+// Сразу после скачка трассы
+pub fn after_trace_jump(jumps: &[f32], time: f32) -> bool {
+    jumps
+        .iter()
+        .any(|&jump| time >= jump && time < jump + TRACE_JUMP_SETTLE)
+}
 
-            if direction.length() > 0.05 {
-                total_direction += direction;
-                count += 1;
-            }
+// Трасса, разрезанная по скачкам; тик скачка начинает новый отрезок
+pub fn trace_segments<'a>(data: &'a [DataPoint], jumps: &[f32]) -> Vec<&'a [DataPoint]> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for &jump in jumps {
+        let end = data.partition_point(|p| p.time < jump).max(start);
+        if end > start {
+            segments.push(&data[start..end]);
         }
+        start = end;
     }
+    segments.push(&data[start..]);
+    segments
+}
 
-    if count > 0 {
-        Some(total_direction / count as f32)
-    } else {
-        None
+// Детектор по каждому отрезку отдельно: сглаживание и поиск реакции не переходят через скачок
+fn detect_segmented(
+    detector: &dyn ReactionDetector,
+    data: &[DataPoint],
+    jumps: &[f32],
+) -> Detection {
+    let mut detection = Detection::default();
+    for segment in trace_segments(data, jumps) {
+        let part = detector.detect(segment);
+        detection.changes.extend(
+            part.changes
+                .into_iter()
+                .filter(|c| !after_trace_jump(jumps, c.time)),
+        );
+        detection.reactions.extend(
+            part.reactions
+                .into_iter()
+                .filter(|r| !after_trace_jump(jumps, r.change_time)),
+        );
     }
+    detection
 }
 
-// Вычисляет сглаженное направление движения прицела в заданном диапазоне
-fn calculate_smoothed_crosshair_direction(
-    test: &ReactionTest,
-    start_idx: usize,
-    end_idx: usize,
-) -> Vec2 {
-    if start_idx >= end_idx || end_idx >= test.data.len() {
-        return Vec2::ZERO;
+pub fn analyze_reaction_delay(test: &mut ReactionTest, detectors: &DetectorRegistry) {
+    if test.data.len() < 100 {
+        return;
     }
 
-    let mut total_direction = Vec2::ZERO;
-    let mut count = 0;
+    // Все зарегистрированные детекторы, чтобы их можно было сравнить между собой.
+    // Основные метрики - от активного детектора, его результат берем из того же прохода
+    let jumps = trace_jumps(&test.target_switches, &test.kills);
+    let active = detectors.active().name().to_string();
+    let mut active_detection = Detection::default();
+    test.detector_reports = detectors
+        .iter()
+        .map(|detector| {
+            let detection = detect_segmented(detector, &test.data, &jumps);
+            let report = DetectorReport::new(detector.name(), &detection);
+            if detector.name() == active {
+                active_detection = detection;
+            }
+            report
+        })
+        .collect();
 
-    for i in start_idx..(end_idx - 1) {
-        let dt = test.data[i + 1].time - test.data[i].time;
-        if dt > 0.001 {
-            let direction = Vec2::new(
-                test.data[i + 1].crosshair_x - test.data[i].crosshair_x,
-                test.data[i + 1].crosshair_y - test.data[i].crosshair_y,
-            ) / dt;
+    let detection = active_detection;
+    let report = DetectorReport::new(&active, &detection);
 
-            if direction.length() > 0.05 {
-                total_direction += direction;
-                count += 1;
-            }
-        }
-    }
+    // Без реакций задержки нулевые, а не от прошлого запуска
+    test.average_delay = report.average_delay;
+    test.median_delay = report.median_delay;
+    test.react_directions = report.react_directions;
+    test.count_directions = report.count_directions;
+    test.delay_stats = delay_stats(&detection);
 
-    if count > 0 {
-        total_direction / count as f32
-    } else {
-        Vec2::ZERO
-    }
+    test.direction_changes = detection.changes;
+    test.reactions = detection.reactions;
 }
 
 pub fn calculate_angular_error(target_position: Vec3, crosshair_direction: Vec3) -> f32 {
@@ -500,6 +655,11 @@ fn calculate_peak_angular_error_simple(test: &ReactionTest) -> f32 {
         .fold(0.0, |acc, error| acc.max(error))
 }
 
+// Попадает ли луч из камеры в сферический таргет
+pub fn ray_hits_target(ray_origin: Vec3, ray_direction: Vec3, center: Vec3, radius: f32) -> bool {
+    ray_sphere_intersection(ray_origin, ray_direction, Sphere { center, radius })
+}
+
 fn ray_sphere_intersection(ray_origin: Vec3, ray_direction: Vec3, sphere: Sphere) -> bool {
     let oc = ray_origin - sphere.center;
     let a = ray_direction.dot(ray_direction);
@@ -13,17 +13,19 @@ pub fn handle_mouse_lock(
     if let Ok(mut window) = windows.single_mut() {
         match *app_state.get() {
             AppState::Game => {
-                if test.is_running && !state.cursor_locked {
+                // На паузе курсор свободен
+                let playing = test.is_running && !test.paused;
+                if playing && !state.cursor_locked {
                     window.cursor_options.grab_mode = CursorGrabMode::Locked;
                     window.cursor_options.visible = false;
                     state.cursor_locked = true;
-                } else if !test.is_running && state.cursor_locked {
+                } else if !playing && state.cursor_locked {
                     window.cursor_options.grab_mode = CursorGrabMode::None;
                     window.cursor_options.visible = true;
                     state.cursor_locked = false;
                 }
             }
-            AppState::Settings => {
+            AppState::Settings | AppState::Results => {
                 window.cursor_options.grab_mode = CursorGrabMode::None;
                 window.cursor_options.visible = true;
                 if state.cursor_locked {
@@ -41,18 +43,35 @@ pub fn handle_mouse_lock(
     }
 }
 
+// Стандартные материалы таргетов, без арены
+type TargetMaterials<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static TargetIndex,
+        &'static MeshMaterial3d<StandardMaterial>,
+    ),
+    (With<Target>, Without<Arena>),
+>;
+
+// Материал таргета: обычный или с эффектом Френеля
+type TargetMaterialHandles = (
+    Option<&'static MeshMaterial3d<StandardMaterial>>,
+    Option<&'static MeshMaterial3d<ExtendedMaterial>>,
+);
+
 pub fn apply_theme_to_scene(
     settings: Res<Settings>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     arena_query: Query<&MeshMaterial3d<StandardMaterial>, With<Arena>>,
-    target_query: Query<&MeshMaterial3d<StandardMaterial>, (With<Target>, Without<Arena>)>,
+    target_query: TargetMaterials,
 ) {
     if !settings.is_changed() {
         return;
     }
 
     let arena_color = settings.get_arena_color();
-    let target_color = settings.get_target_color();
+    let target_count = target_query.iter().count();
 
     for handle in arena_query.iter() {
         if let Some(mat) = materials.get_mut(&handle.0) {
@@ -60,9 +79,9 @@ pub fn apply_theme_to_scene(
         }
     }
 
-    for handle in target_query.iter() {
+    for (index, handle) in target_query.iter() {
         if let Some(mat) = materials.get_mut(&handle.0) {
-            mat.base_color = target_color;
+            mat.base_color = target_color_for(&settings, index.0, target_count);
         }
     }
 }
@@ -94,8 +113,9 @@ pub fn update_fresnel_target_material(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut extended_materials: ResMut<Assets<ExtendedMaterial>>,
-    target_query: Query<(Entity, &Transform), With<Target>>,
+    target_query: Query<(Entity, &Transform, &TargetIndex), With<Target>>,
     test: Res<ReactionTest>,
+    scenarios: Res<ScenarioLibrary>,
 ) {
     let current_fresnel_color = settings.get_fresnel_color();
 
@@ -118,133 +138,68 @@ pub fn update_fresnel_target_material(
     fresnel_tracker.last_fresnel_intensity = settings.fresnel_intensity;
     fresnel_tracker.last_fresnel_power = settings.fresnel_power;
 
-    // Get current target position before despawning
-    let current_position = if let Ok((_, transform)) = target_query.get_single() {
-        transform.translation
-    } else if test.is_running {
-        // If test is running, use test target position
-        test.target_position
-    } else {
-        Vec3::ZERO
-    };
+    // Get current target positions before despawning
+    let mut positions: Vec<Vec3> = test.targets.iter().map(|t| t.position).collect();
+    for (_, transform, index) in target_query.iter() {
+        if index.0 >= positions.len() {
+            positions.resize(index.0 + 1, Vec3::ZERO);
+        }
+        positions[index.0] = transform.translation;
+    }
 
     // Only despawn if target exists
-    for (entity, _) in target_query.iter() {
+    for (entity, _, _) in target_query.iter() {
         commands.entity(entity).despawn();
     }
 
-    let target_color = settings.get_target_color();
-
-    // Spawn new target with appropriate material and preserve position
-    if settings.fresnel_enabled {
-        let fresnel_material = FresnelMaterial {
-            fresnel_color: Vec4::new(
-                current_fresnel_color.to_linear().red,
-                current_fresnel_color.to_linear().green,
-                current_fresnel_color.to_linear().blue,
-                settings.fresnel_intensity, // intensity in alpha channel
-            ),
-            fresnel_params: Vec4::new(
-                settings.fresnel_power,
-                1.0, // enabled
-                0.0, // unused
-                0.0, // unused
-            ),
-        };
-
-        commands.spawn((
-            Mesh3d(meshes.add(Mesh::from(bevy::math::primitives::Sphere {
-                radius: TARGET_SIZE,
-            }))),
-            MeshMaterial3d(extended_materials.add(ExtendedMaterial {
-                base: StandardMaterial {
-                    base_color: target_color,
-                    unlit: false,
-                    ..default()
-                },
-                extension: fresnel_material,
-            })),
-            Transform::from_translation(current_position),
-            Visibility::default(),
-            Target,
-        ));
-    } else {
-        commands.spawn((
-            Mesh3d(meshes.add(Mesh::from(bevy::math::primitives::Sphere {
-                radius: TARGET_SIZE,
-            }))),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: target_color,
-                unlit: false,
-                ..default()
-            })),
-            Transform::from_translation(current_position),
-            Visibility::default(),
-            Target,
-        ));
-    }
+    // Spawn new targets with appropriate material and preserve positions
+    spawn_targets(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut extended_materials,
+        &settings,
+        &scenarios.get(&settings.scenario),
+        &positions,
+    );
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn refresh_target_on_game_enter(
     settings: Res<Settings>,
+    scenarios: Res<ScenarioLibrary>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut extended_materials: ResMut<Assets<ExtendedMaterial>>,
     target_query: Query<Entity, With<Target>>,
     mut fresnel_tracker: ResMut<FresnelTracker>,
+    ghost: Res<GhostRace>,
 ) {
     // Always refresh target when entering game to ensure proper material
     for entity in target_query.iter() {
         commands.entity(entity).despawn();
     }
 
-    let target_color = settings.get_target_color();
     let fresnel_color = settings.get_fresnel_color();
 
-    // Spawn target with correct material based on current settings
-    if settings.fresnel_enabled {
-        let fresnel_material = FresnelMaterial {
-            fresnel_color: Vec4::new(
-                fresnel_color.to_linear().red,
-                fresnel_color.to_linear().green,
-                fresnel_color.to_linear().blue,
-                settings.fresnel_intensity,
-            ),
-            fresnel_params: Vec4::new(settings.fresnel_power, 1.0, 0.0, 0.0),
-        };
-
-        commands.spawn((
-            Mesh3d(meshes.add(Mesh::from(bevy::math::primitives::Sphere {
-                radius: TARGET_SIZE,
-            }))),
-            MeshMaterial3d(extended_materials.add(ExtendedMaterial {
-                base: StandardMaterial {
-                    base_color: target_color,
-                    unlit: false,
-                    ..default()
-                },
-                extension: fresnel_material,
-            })),
-            Transform::from_translation(Vec3::ZERO),
-            Visibility::default(),
-            Target,
-        ));
+    // В гонке таргеты по сценарию призрака: он может отличаться от выбранного
+    let scenario = if ghost.is_active() {
+        ghost.scenario.clone()
     } else {
-        commands.spawn((
-            Mesh3d(meshes.add(Mesh::from(bevy::math::primitives::Sphere {
-                radius: TARGET_SIZE,
-            }))),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: target_color,
-                unlit: false,
-                ..default()
-            })),
-            Transform::from_translation(Vec3::ZERO),
-            Visibility::default(),
-            Target,
-        ));
-    }
+        scenarios.get(&settings.scenario)
+    };
+
+    // Spawn targets with correct material based on current settings
+    spawn_targets(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut extended_materials,
+        &settings,
+        &scenario,
+        &[],
+    );
 
     // Update tracker to current settings
     fresnel_tracker.last_fresnel_enabled = settings.fresnel_enabled;
@@ -252,3 +207,180 @@ pub fn refresh_target_on_game_enter(
     fresnel_tracker.last_fresnel_intensity = settings.fresnel_intensity;
     fresnel_tracker.last_fresnel_power = settings.fresnel_power;
 }
+
+// Цвет таргета: первый - из настроек, остальные со сдвигом тона, чтобы их различать
+pub fn target_color_for(settings: &Settings, index: usize, count: usize) -> Color {
+    let color = settings.get_target_color();
+    if index == 0 || count < 2 {
+        return color;
+    }
+    Hsla::from(color)
+        .rotate_hue(360.0 * index as f32 / count as f32)
+        .into()
+}
+
+// Спавнит таргеты сценария; позиции по индексу, недостающие - в центре
+pub fn spawn_targets(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    extended_materials: &mut Assets<ExtendedMaterial>,
+    settings: &Settings,
+    scenario: &Scenario,
+    positions: &[Vec3],
+) {
+    let fresnel_color = settings.get_fresnel_color();
+    let mesh = meshes.add(Mesh::from(bevy::math::primitives::Sphere {
+        radius: scenario.target_size,
+    }));
+    let count = scenario.target_count.max(1);
+
+    for index in 0..count {
+        let base = StandardMaterial {
+            base_color: target_color_for(settings, index, count),
+            unlit: false,
+            ..default()
+        };
+        let position = positions.get(index).copied().unwrap_or(Vec3::ZERO);
+        let mut entity = commands.spawn((
+            Mesh3d(mesh.clone()),
+            Transform::from_translation(position),
+            Visibility::default(),
+            Target,
+            TargetIndex(index),
+        ));
+
+        if settings.fresnel_enabled {
+            let fresnel_material = FresnelMaterial {
+                fresnel_color: Vec4::new(
+                    fresnel_color.to_linear().red,
+                    fresnel_color.to_linear().green,
+                    fresnel_color.to_linear().blue,
+                    settings.fresnel_intensity, // intensity in alpha channel
+                ),
+                fresnel_params: Vec4::new(
+                    settings.fresnel_power,
+                    1.0, // enabled
+                    0.0, // unused
+                    0.0, // unused
+                ),
+            };
+            entity.insert(MeshMaterial3d(extended_materials.add(ExtendedMaterial {
+                base,
+                extension: fresnel_material,
+            })));
+        } else {
+            entity.insert(MeshMaterial3d(materials.add(base)));
+        }
+    }
+}
+
+// Активный таргет светится, остальные приглушены (только когда таргетов несколько)
+pub fn highlight_active_target(
+    test: Res<ReactionTest>,
+    settings: Res<Settings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut extended_materials: ResMut<Assets<ExtendedMaterial>>,
+    target_query: Query<(&TargetIndex, TargetMaterialHandles)>,
+    added_query: Query<(), Added<TargetIndex>>,
+    mut last_active: Local<Option<usize>>,
+) {
+    let count = target_query.iter().count();
+    if count < 2 {
+        return;
+    }
+    if *last_active == Some(test.active_target) && added_query.is_empty() && !settings.is_changed()
+    {
+        return;
+    }
+    *last_active = Some(test.active_target);
+
+    for (index, (standard, extended)) in target_query.iter() {
+        let color = target_color_for(&settings, index.0, count);
+        let (base_color, emissive) = if index.0 == test.active_target {
+            (color, LinearRgba::from(color) * TARGET_HIGHLIGHT_EMISSIVE)
+        } else {
+            let dimmed = Hsla::from(color);
+            (
+                dimmed.with_lightness(dimmed.lightness * 0.4).into(),
+                LinearRgba::BLACK,
+            )
+        };
+
+        if let Some(mat) = standard.and_then(|h| materials.get_mut(&h.0)) {
+            mat.base_color = base_color;
+            mat.emissive = emissive;
+        }
+        if let Some(mat) = extended.and_then(|h| extended_materials.get_mut(&h.0)) {
+            mat.base.base_color = base_color;
+            mat.base.emissive = emissive;
+        }
+    }
+}
+
+// Цвет стимула режимов реакции: 0 - цвет таргета, остальные - с повернутым тоном
+pub fn reaction_stimulus_color(settings: &Settings, mode: TestMode, stimulus: usize) -> Color {
+    match mode {
+        TestMode::Choice => target_color_for(settings, stimulus, 2),
+        TestMode::GoNoGo => target_color_for(settings, stimulus, REACTION_STIMULUS_COLORS),
+        _ => target_color_for(settings, 0, 1),
+    }
+}
+
+// Режимы реакции: до стимула таргет тусклый и серый, чтобы не подсказывать цвет,
+// после - светится цветом стимула (при слуховом стимуле остается серым).
+// Вне теста возвращаем обычный цвет
+pub fn show_reaction_stimulus(
+    test: Res<ReactionTest>,
+    settings: Res<Settings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut extended_materials: ResMut<Assets<ExtendedMaterial>>,
+    target_query: Query<TargetMaterialHandles, With<Target>>,
+    added_query: Query<(), Added<TargetIndex>>,
+    mut last_state: Local<Option<(bool, Option<usize>)>>,
+) {
+    if !test.scenario.mode.is_reaction() {
+        *last_state = None;
+        return;
+    }
+    let shown = test
+        .reaction_trial
+        .as_ref()
+        .filter(|t| t.stimulus_shown && !t.auditory)
+        .map(|t| t.stimulus);
+    let state = (test.is_running, shown);
+    if *last_state == Some(state) && added_query.is_empty() && !settings.is_changed() {
+        return;
+    }
+    *last_state = Some(state);
+
+    let color = target_color_for(&settings, 0, 1);
+    let (base_color, emissive) = match state {
+        (true, Some(stimulus)) => {
+            let color = reaction_stimulus_color(&settings, test.scenario.mode, stimulus);
+            (color, LinearRgba::from(color) * TARGET_HIGHLIGHT_EMISSIVE)
+        }
+        (true, None) => {
+            let dimmed = Hsla::from(color);
+            (
+                dimmed
+                    .with_saturation(0.0)
+                    .with_lightness(dimmed.lightness * 0.4)
+                    .into(),
+                LinearRgba::BLACK,
+            )
+        }
+        (false, _) => (color, LinearRgba::BLACK),
+    };
+
+    for (standard, extended) in target_query.iter() {
+        if let Some(mat) = standard.and_then(|h| materials.get_mut(&h.0)) {
+            mat.base_color = base_color;
+            mat.emissive = emissive;
+        }
+        if let Some(mat) = extended.and_then(|h| extended_materials.get_mut(&h.0)) {
+            mat.base.base_color = base_color;
+            mat.base.emissive = emissive;
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::state::*;
+use crate::stats;
+use crate::target::{after_trace_jump, trace_jumps};
+use bevy::prelude::*;
+use rayon::prelude::*;
+
+// Максимальная задержка, которую ищем, секунды
+pub const XCORR_MAX_LAG: f32 = 0.6;
+// Длина сегмента для посегментной оценки, секунды
+pub const XCORR_SEGMENT_DURATION: f32 = 2.0;
+// Сегменты со слабой корреляцией не учитываются
+pub const XCORR_MIN_CORRELATION: f32 = 0.3;
+// Полуокно сглаживания скорости, в тиках
+const VELOCITY_HALF_WINDOW: usize = 5;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XcorrEstimate {
+    // Задержка (секунды), при которой корреляция максимальна
+    pub lag: f32,
+    // Нормированная корреляция в этой точке, [-1, 1]
+    pub correlation: f32,
+}
+
+// Оценка задержки по максимуму взаимной корреляции скоростей таргета и прицела
+pub fn analyze_cross_correlation(test: &mut ReactionTest) {
+    test.xcorr_delay = 0.0;
+    test.xcorr_correlation = 0.0;
+    test.xcorr_segment_delays.clear();
+    test.xcorr_segment_count = 0;
+
+    let jumps = trace_jumps(&test.target_switches, &test.kills);
+    let Some((target_vel, crosshair_vel, dt)) = angular_velocity_traces(&test.data, &jumps) else {
+        return;
+    };
+    let max_lag = (XCORR_MAX_LAG / dt).round() as usize;
+
+    if let Some(estimate) = estimate_lag(&target_vel, &crosshair_vel, max_lag, dt) {
+        test.xcorr_delay = estimate.lag * 1000.0;
+        test.xcorr_correlation = estimate.correlation;
+    }
+
+    // Посегментно: задержка в каждом окне отдельно
+    let segment_len = (XCORR_SEGMENT_DURATION / dt).round() as usize;
+    if segment_len <= max_lag {
+        return;
+    }
+    let mut start = 0;
+    while start + segment_len <= target_vel.len() {
+        test.xcorr_segment_count += 1;
+        // Прицел берем с запасом на максимальную задержку
+        let end = (start + segment_len + max_lag).min(crosshair_vel.len());
+        if let Some(estimate) = estimate_lag(
+            &target_vel[start..start + segment_len],
+            &crosshair_vel[start..end],
+            max_lag,
+            dt,
+        ) {
+            if estimate.correlation >= XCORR_MIN_CORRELATION {
+                test.xcorr_segment_delays.push(estimate.lag * 1000.0);
+            }
+        }
+        start += segment_len;
+    }
+}
+
+// Угловые скорости (рад/с) таргета и прицела, сглаженные центральной разностью.
+// Окна со скачком трассы и переброс прицела после него обнуляются
+fn angular_velocity_traces(
+    data: &[DataPoint],
+    jumps: &[f32],
+) -> Option<(Vec<Vec2>, Vec<Vec2>, f32)> {
+    let w = VELOCITY_HALF_WINDOW;
+    if data.len() < 4 * w + 2 {
+        return None;
+    }
+
+    let dt = (data[data.len() - 1].time - data[0].time) / (data.len() - 1) as f32;
+    if dt <= 0.0 {
+        return None;
+    }
+
+    let target_angles: Vec<Vec2> = data
+        .iter()
+        .map(|p| direction_to_angles(p.target_pos - p.camera_pos))
+        .collect();
+    let crosshair_angles: Vec<Vec2> = data
+        .iter()
+        .map(|p| direction_to_angles(p.crosshair_dir))
+        .collect();
+
+    let velocity = |angles: &[Vec2]| -> Vec<Vec2> {
+        (w..angles.len() - w)
+            .map(|i| (angles[i + w] - angles[i - w]) / (2 * w) as f32 / dt)
+            .collect()
+    };
+
+    let masked: Vec<bool> = (w..data.len() - w)
+        .map(|i| {
+            let (from, to) = (data[i - w].time, data[i + w].time);
+            jumps.iter().any(|&jump| jump > from && jump <= to)
+                || after_trace_jump(jumps, data[i].time)
+        })
+        .collect();
+
+    let mut target_vel = velocity(&target_angles);
+    let mut crosshair_vel = velocity(&crosshair_angles);
+    remove_mean(&mut target_vel, &masked);
+    remove_mean(&mut crosshair_vel, &masked);
+
+    Some((target_vel, crosshair_vel, dt))
+}
+
+// Направление -> (yaw, pitch) относительно -Z
+fn direction_to_angles(direction: Vec3) -> Vec2 {
+    let d = direction.normalize_or_zero();
+    Vec2::new(d.x.atan2(-d.z), d.y.clamp(-1.0, 1.0).asin())
+}
+
+// Среднее - по необнуленным отсчетам; обнуленные остаются нулями и не дают вклада в корреляцию
+fn remove_mean(values: &mut [Vec2], masked: &[bool]) {
+    let kept = masked.iter().filter(|m| !**m).count();
+    if kept == 0 {
+        values.fill(Vec2::ZERO);
+        return;
+    }
+    let mean = values
+        .iter()
+        .zip(masked)
+        .filter(|(_, m)| !**m)
+        .map(|(v, _)| *v)
+        .sum::<Vec2>()
+        / kept as f32;
+    for (v, m) in values.iter_mut().zip(masked) {
+        *v = if *m { Vec2::ZERO } else { *v - mean };
+    }
+}
+
+// Перебирает задержки 0..=max_lag тиков; crosshair может быть длиннее target на max_lag
+pub fn estimate_lag(
+    target: &[Vec2],
+    crosshair: &[Vec2],
+    max_lag: usize,
+    dt: f32,
+) -> Option<XcorrEstimate> {
+    let correlations: Vec<(usize, f32)> = (0..=max_lag)
+        .into_par_iter()
+        .filter_map(|lag| {
+            let n = target.len().min(crosshair.len().saturating_sub(lag));
+            if n < 2 {
+                return None;
+            }
+            let mut cross = 0.0;
+            let mut target_energy = 0.0;
+            let mut crosshair_energy = 0.0;
+            for i in 0..n {
+                let t = target[i];
+                let c = crosshair[i + lag];
+                cross += t.dot(c);
+                target_energy += t.length_squared();
+                crosshair_energy += c.length_squared();
+            }
+            let norm = (target_energy * crosshair_energy).sqrt();
+            (norm > f32::EPSILON).then(|| (lag, cross / norm))
+        })
+        .collect();
+
+    correlations
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(lag, correlation)| XcorrEstimate {
+            lag: lag as f32 * dt,
+            correlation,
+        })
+}
+
+// Медиана посегментных задержек, мс
+pub fn xcorr_segment_median(test: &ReactionTest) -> Option<f32> {
+    stats::median(&test.xcorr_segment_delays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Скорость как у random-turn: постоянная, со сменой направления каждые 150 тиков
+    fn turning_velocity(ticks: usize) -> Vec<Vec2> {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut velocity = Vec2::ZERO;
+        (0..ticks)
+            .map(|i| {
+                if i % 150 == 0 {
+                    velocity = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+                }
+                velocity
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recovers_known_shift() {
+        let (dt, shift, max_lag) = (0.001, 120, 600);
+        let target = turning_velocity(3000);
+        // Прицел повторяет таргет с опозданием на shift тиков
+        let mut crosshair = vec![Vec2::ZERO; shift];
+        crosshair.extend(&target);
+        crosshair.resize(target.len() + max_lag, Vec2::ZERO);
+
+        let estimate = estimate_lag(&target, &crosshair, max_lag, dt).unwrap();
+        assert!((estimate.lag - shift as f32 * dt).abs() < 1e-6);
+        assert!(estimate.correlation > 0.99);
+    }
+
+    #[test]
+    fn silent_trace_has_no_estimate() {
+        let target = turning_velocity(1000);
+        assert!(estimate_lag(&target, &vec![Vec2::ZERO; 1600], 600, 0.001).is_none());
+    }
+}
@@ -4,29 +4,64 @@ use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 use bevy::prelude::*;
 use bevy::window::PresentMode;
 
+mod cli;
 mod config;
 mod kernel;
 mod rendering;
 mod state;
+mod storage;
 mod user_interface;
 
 use config::*;
 use kernel::*;
 use rendering::*;
 use state::*;
+use storage::*;
 use user_interface::*;
 
 use config::config::*;
 use config::settings::*;
 use kernel::camera::*;
+use kernel::detector::*;
+use kernel::flick::*;
 use kernel::game::*;
+use kernel::ghost::*;
+use kernel::reaction::*;
+use kernel::replay::*;
 use kernel::target::*;
 use kernel::utils::*;
 use rendering::fresnel::*;
+use storage::export::*;
+use storage::history::*;
+use user_interface::results::*;
 use user_interface::ui::*;
 use user_interface::ui_components::*;
 
 fn main() {
+    // Подкоманды (analyze, simulate, validate) работают без окна
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // `replay <run.json>` открывает окно сразу с просмотром записи
+    let replay = cli::replay_from_args(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    // `ghost <run.json>` открывает окно с гонкой против записи
+    let ghost = cli::ghost_from_args(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    let replay = match replay {
+        Some(replay) => replay,
+        None => {
+            if ghost.is_none() {
+                if let Some(code) = cli::run_cli(&args) {
+                    std::process::exit(code);
+                }
+            }
+            ReplayViewer::default()
+        }
+    };
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -50,6 +85,13 @@ fn main() {
         .init_resource::<InputFocus>()
         .init_resource::<FpsUiState>()
         .init_resource::<FresnelTracker>()
+        .add_event::<TestFinishedEvent>()
+        .init_resource::<RunHistory>()
+        .insert_resource(replay)
+        .insert_resource(ghost.unwrap_or_default())
+        .init_resource::<DetectorRegistry>()
+        .add_systems(OnEnter(AppState::Loading), load_history_system)
+        .add_systems(OnExit(AppState::Loading), configure_detectors)
         // Loading
         .add_systems(Update, load_app.run_if(in_state(AppState::Loading)))
         .add_systems(
@@ -57,6 +99,7 @@ fn main() {
             (
                 settings_input_system.run_if(in_state(AppState::Settings)),
                 game_input_system.run_if(in_state(AppState::Game)),
+                pause_on_focus_loss.run_if(in_state(AppState::Game)),
             ),
         )
         .add_systems(
@@ -67,6 +110,9 @@ fn main() {
                 update_game_ui,
                 update_button_styles,
                 update_arena_walls_color,
+                highlight_active_target.after(apply_theme_to_scene),
+                show_reaction_stimulus.after(apply_theme_to_scene),
+                measure_sound_start_delay,
             )
                 .run_if(in_state(AppState::Game)),
         )
@@ -89,7 +135,49 @@ fn main() {
         )
         .add_systems(Update, handle_color_picker_escape)
         .add_systems(Update, (update_fps_ui,))
-        .add_systems(FixedUpdate, update_target.run_if(in_state(AppState::Game)))
+        .add_systems(
+            FixedUpdate,
+            (update_target, update_flick, update_reaction).run_if(in_state(AppState::Game)),
+        )
+        .add_systems(
+            Update,
+            (export_finished_run, record_run_history).before(game_input_system),
+        )
+        .add_systems(
+            Update,
+            (
+                replay_input_system.after(game_input_system),
+                scrub_replay_timeline,
+                sync_replay_scene,
+                update_replay,
+            )
+                .chain()
+                .run_if(in_state(AppState::Game)),
+        )
+        .add_systems(
+            Update,
+            (
+                show_results_on_finish.run_if(in_state(AppState::Game)),
+                (results_input_system, update_results_hint)
+                    .chain()
+                    .run_if(in_state(AppState::Results)),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                ghost_input_system.after(game_input_system),
+                update_ghost.run_if(in_state(AppState::Game)),
+            )
+                .chain()
+                .run_if(in_state(AppState::Game).or(in_state(AppState::Results))),
+        )
+        .add_systems(
+            Update,
+            (toggle_history_view, update_history_ui)
+                .chain()
+                .run_if(in_state(AppState::Game).or(in_state(AppState::Results))),
+        )
         .add_systems(
             Update,
             (
@@ -105,6 +193,7 @@ fn main() {
                 settings_button_system.run_if(in_state(AppState::Settings)),
                 update_settings_text.run_if(in_state(AppState::Settings)),
                 settings_slider_system.run_if(in_state(AppState::Settings)),
+                handle_scenario_selector.run_if(in_state(AppState::Settings)),
             ),
         )
         .add_systems(
@@ -117,6 +206,9 @@ fn main() {
                 fresnel_power_input_box_system.run_if(in_state(AppState::Settings)),
                 directional_light_input_box_system.run_if(in_state(AppState::Settings)),
                 ambient_light_input_box_system.run_if(in_state(AppState::Settings)),
+                seed_input_box_system.run_if(in_state(AppState::Settings)),
+                test_duration_input_box_system.run_if(in_state(AppState::Settings)),
+                countdown_input_box_system.run_if(in_state(AppState::Settings)),
                 clear_other_editing_states_system.run_if(in_state(AppState::Settings)),
                 update_fresnel_ui.run_if(in_state(AppState::Settings)),
             ),
@@ -126,14 +218,23 @@ fn main() {
         .add_systems(OnEnter(AppState::Settings), restart_test_on_settings_enter)
         .add_systems(OnExit(AppState::Settings), cleanup_settings_ui)
         .add_systems(OnExit(AppState::Settings), restart_test_on_settings_exit)
+        // Сцена игры живет и под экраном результатов: оттуда сразу стартует новый тест
         .add_systems(
             OnEnter(AppState::Game),
-            (setup_game_scene, refresh_target_on_game_enter).chain(),
+            (setup_game_scene, refresh_target_on_game_enter)
+                .chain()
+                .run_if(not(any_with_component::<PlayerCamera>)),
         )
-        .add_systems(OnExit(AppState::Game), cleanup_game_scene)
+        .add_systems(OnEnter(AppState::Settings), cleanup_game_scene)
+        .add_systems(OnEnter(AppState::Results), setup_results_ui)
+        .add_systems(OnExit(AppState::Results), cleanup_results_ui)
         .run();
 }
 
-fn load_app(mut next_state: ResMut<NextState<AppState>>) {
-    next_state.set(AppState::Settings);
+fn load_app(mut next_state: ResMut<NextState<AppState>>, replay: Res<ReplayViewer>) {
+    if replay.active {
+        next_state.set(AppState::Game);
+    } else {
+        next_state.set(AppState::Settings);
+    }
 }
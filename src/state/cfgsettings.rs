@@ -22,6 +22,12 @@ pub struct GameConfig {
     pub texture_file: String,
     pub font_file: String,
     pub hit_sound_file: String,
+    // Звуки стимула в режиме слуховой реакции, по очереди
+    #[serde(default = "default_stimulus_sound_files")]
+    pub stimulus_sound_files: Vec<String>,
+    // Задержка аудиовыхода (буфер устройства), мс; добавляется к измеренной
+    #[serde(default)]
+    pub audio_output_latency: f32,
     #[serde(
         serialize_with = "serialize_keycode",
         deserialize_with = "deserialize_keycode"
@@ -42,6 +48,118 @@ pub struct GameConfig {
         deserialize_with = "deserialize_keycode"
     )]
     pub key_fullscreen: KeyCode,
+    #[serde(
+        default = "default_key_history",
+        serialize_with = "serialize_keycode",
+        deserialize_with = "deserialize_keycode"
+    )]
+    pub key_history: KeyCode,
+    #[serde(
+        default = "default_key_replay",
+        serialize_with = "serialize_keycode",
+        deserialize_with = "deserialize_keycode"
+    )]
+    pub key_replay: KeyCode,
+    #[serde(
+        default = "default_key_ghost",
+        serialize_with = "serialize_keycode",
+        deserialize_with = "deserialize_keycode"
+    )]
+    pub key_ghost: KeyCode,
+    #[serde(
+        default = "default_key_shoot",
+        serialize_with = "serialize_mouse_button",
+        deserialize_with = "deserialize_mouse_button"
+    )]
+    pub key_shoot: MouseButton,
+    #[serde(default = "default_scenario_name")]
+    pub scenario: String,
+    #[serde(default)]
+    pub seed: u64,
+    // Длительность теста, секунды; 0 - как в сценарии
+    #[serde(default)]
+    pub test_duration: f32,
+    // Обратный отсчет перед тестом, секунды
+    #[serde(default = "default_countdown")]
+    pub countdown: f32,
+    #[serde(default = "default_detector_name")]
+    pub detector: String,
+    #[serde(default)]
+    pub heuristic_detector: HeuristicDetectorConfig,
+}
+
+// Пороги эвристического детектора реакций
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct HeuristicDetectorConfig {
+    // Окно сглаживания траектории таргета, тики
+    pub target_window: usize,
+    // Минимальный поворот таргета, градусы
+    pub min_target_turn_angle: f32,
+    // Минимальная скорость таргета для анализа
+    pub min_target_speed: f32,
+    // Смены ближе этого интервала считаются одной, секунды
+    pub duplicate_window: f32,
+    // Окно поиска реакции после смены направления, секунды
+    pub reaction_search_start: f32,
+    pub reaction_search_end: f32,
+    // Окно сглаживания движения прицела, тики
+    pub crosshair_window: usize,
+    // Минимальный поворот прицела, градусы
+    pub min_crosshair_turn_angle: f32,
+    // Насколько новое движение прицела должно лучше совпадать с таргетом
+    pub min_alignment_gain: f32,
+    // Окно до смены направления для оценки старого движения прицела, секунды
+    pub pre_change_window: f32,
+}
+
+impl Default for HeuristicDetectorConfig {
+    fn default() -> Self {
+        Self {
+            target_window: 7,
+            min_target_turn_angle: 35.0,
+            min_target_speed: 0.2,
+            duplicate_window: 0.08,
+            reaction_search_start: 0.07,
+            reaction_search_end: 0.8,
+            crosshair_window: 3,
+            min_crosshair_turn_angle: 25.0,
+            min_alignment_gain: 0.05,
+            pre_change_window: 0.15,
+        }
+    }
+}
+
+fn default_detector_name() -> String {
+    String::from("heuristic")
+}
+
+fn default_key_history() -> KeyCode {
+    KeyCode::KeyH
+}
+
+fn default_key_replay() -> KeyCode {
+    KeyCode::KeyP
+}
+
+fn default_key_ghost() -> KeyCode {
+    KeyCode::KeyG
+}
+
+fn default_key_shoot() -> MouseButton {
+    MouseButton::Left
+}
+
+fn default_stimulus_sound_files() -> Vec<String> {
+    vec![String::from("hit.ogg")]
+}
+
+fn default_scenario_name() -> String {
+    String::from("Default")
+}
+
+fn default_countdown() -> f32 {
+    COUNTDOWN_DURATION
 }
 
 impl Default for GameConfig {
@@ -64,10 +182,22 @@ impl Default for GameConfig {
             texture_file: String::from("texture.png"),
             font_file: String::from("font.ttf"),
             hit_sound_file: String::from("hit.ogg"),
+            stimulus_sound_files: default_stimulus_sound_files(),
+            audio_output_latency: 0.0,
             key_restart: KeyCode::KeyR,
             key_start: KeyCode::Space,
             key_settings: KeyCode::Escape,
             key_fullscreen: KeyCode::F12,
+            key_history: default_key_history(),
+            key_replay: default_key_replay(),
+            key_ghost: default_key_ghost(),
+            key_shoot: default_key_shoot(),
+            scenario: default_scenario_name(),
+            seed: 0,
+            test_duration: 0.0,
+            countdown: default_countdown(),
+            detector: default_detector_name(),
+            heuristic_detector: HeuristicDetectorConfig::default(),
         }
     }
 }
@@ -219,3 +349,33 @@ where
         _ => KeyCode::F35,
     })
 }
+
+pub fn serialize_mouse_button<S>(button: &MouseButton, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let button_str = match button {
+        MouseButton::Left => "Left",
+        MouseButton::Right => "Right",
+        MouseButton::Middle => "Middle",
+        MouseButton::Back => "Back",
+        MouseButton::Forward => "Forward",
+        MouseButton::Other(_) => "Left",
+    };
+    serializer.serialize_str(button_str)
+}
+
+// Функция для десериализации строки в MouseButton
+pub fn deserialize_mouse_button<'de, D>(deserializer: D) -> Result<MouseButton, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Ok(match s.as_str() {
+        "Right" => MouseButton::Right,
+        "Middle" => MouseButton::Middle,
+        "Back" => MouseButton::Back,
+        "Forward" => MouseButton::Forward,
+        _ => MouseButton::Left, // Значение по умолчанию
+    })
+}
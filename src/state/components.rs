@@ -5,9 +5,28 @@ use bevy::prelude::*;
 #[derive(Component)]
 pub struct Target;
 
+// Номер таргета в ReactionTest::targets
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TargetIndex(pub usize);
+
+// Позиции всех таргетов; без камеры, чтобы не пересекаться с ее Transform
+pub type TargetTransforms<'w, 's> = Query<
+    'w,
+    's,
+    (&'static TargetIndex, &'static mut Transform),
+    (With<Target>, Without<PlayerCamera>),
+>;
+
 #[derive(Component)]
 pub struct PlayerCamera;
 
+// Звук слухового стимула; spawned - момент запуска, для замера задержки старта звука
+#[derive(Component)]
+pub struct StimulusSound {
+    pub trial_start: f32,
+    pub spawned: std::time::Instant,
+}
+
 #[derive(Component)]
 pub struct Crosshair;
 
@@ -35,6 +54,44 @@ pub struct StartCenterText;
 #[derive(Component)]
 pub struct FpsText;
 
+#[derive(Component)]
+pub struct HistoryPanel;
+
+#[derive(Component)]
+pub struct HistoryText;
+
+// Просмотр записи: панель с таймлайном, бегунок и точки следа в сцене
+#[derive(Component)]
+pub struct ReplayPanel;
+
+// Экран результатов
+#[derive(Component)]
+pub struct ResultsUI;
+
+#[derive(Component)]
+pub struct ResultsHintText;
+
+// Полупрозрачный прицел призрака
+#[derive(Component)]
+pub struct GhostMarker;
+
+#[derive(Component)]
+pub struct ReplayInfoText;
+
+#[derive(Component)]
+pub struct ReplayTimeline;
+
+#[derive(Component)]
+pub struct ReplayPlayhead;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayMarker {
+    TargetTrail(usize),
+    CrosshairTrail(usize),
+    DirectionChange(usize),
+    Reaction(usize),
+}
+
 // Settings UI button markers
 #[derive(Component)]
 pub struct BtnArenaColor;
@@ -47,6 +104,32 @@ pub struct BtnBackMenu;
 #[derive(Component)]
 pub struct DiscordLink;
 
+// Scenario selection
+#[derive(Component)]
+pub struct ScenarioSelector;
+#[derive(Component)]
+pub struct ScenarioSelectorText;
+#[derive(Component)]
+pub struct ScenarioDescriptionText;
+#[derive(Component)]
+pub struct SeedInput;
+#[derive(Component)]
+pub struct SeedBuffer(pub String);
+#[derive(Component)]
+pub struct SeedEditing;
+#[derive(Component)]
+pub struct TestDurationInput;
+#[derive(Component)]
+pub struct TestDurationBuffer(pub String);
+#[derive(Component)]
+pub struct TestDurationEditing;
+#[derive(Component)]
+pub struct CountdownInput;
+#[derive(Component)]
+pub struct CountdownBuffer(pub String);
+#[derive(Component)]
+pub struct CountdownEditing;
+
 // Input field components
 #[derive(Component)]
 pub struct DpiInput;
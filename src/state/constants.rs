@@ -2,12 +2,58 @@ pub const ARENA_SIZE: f32 = 32.0;
 pub const TARGET_SIZE: f32 = 0.5;
 pub const TARGET_SPEED: f32 = 7.0;
 pub const TEST_DURATION: f32 = 20.0;
+// Начало теста, которое не идет в анализ (игрок ищет таргет), секунды
+pub const WARMUP_DURATION: f32 = 0.0;
+// Обратный отсчет перед тестом по умолчанию, секунды; 0 - без отсчета
+pub const COUNTDOWN_DURATION: f32 = 0.0;
 
 pub const TARGET_BOUND_X: f32 = 6.0;
 pub const TARGET_BOUND_Y: f32 = 4.0;
 pub const TARGET_BOUND_Z_MIN: f32 = 8.0;
 pub const TARGET_BOUND_Z_MAX: f32 = 16.0;
 
+pub const TARGET_CHANGE_INTERVAL_MIN: f32 = 0.2;
+pub const TARGET_CHANGE_INTERVAL_MAX: f32 = 0.5;
+pub const TARGET_MIN_TURN_ANGLE: f32 = 45.0;
+pub const TARGET_COUNT: usize = 1;
+pub const MAX_TARGET_COUNT: usize = 8;
+// Яркость свечения активного таргета
+pub const TARGET_HIGHLIGHT_EMISSIVE: f32 = 2.0;
+pub const TARGET_SWITCH_INTERVAL_MIN: f32 = 1.0;
+pub const TARGET_SWITCH_INTERVAL_MAX: f32 = 2.0;
+pub const TARGET_DEPTH_SPEED: f32 = 0.0;
+pub const DISTANCE_SWEEP_PERIOD: f32 = 0.0;
+// Разбивка результатов по угловому размеру таргета
+pub const SIZE_BIN_COUNT: usize = 4;
+// Если размер менялся меньше чем на столько градусов, разбивки нет
+pub const SIZE_BIN_MIN_RANGE: f32 = 0.5;
+// Бутстреп доверительного интервала средней задержки
+pub const BOOTSTRAP_RESAMPLES: usize = 2000;
+pub const BOOTSTRAP_SEED: u64 = 0x5eed;
+// Ниже этой доли смен направления с найденной реакцией оценка задержки ненадежна
+pub const MIN_DETECTION_RATE: f32 = 0.5;
+// После скачка трассы (смена таргета, респаун) прицел перебрасывается на новое место:
+// смены направления в это время не анализируются, секунды
+pub const TRACE_JUMP_SETTLE: f32 = 0.3;
+// Границы групп резкости поворота, градусы
+pub const TURN_SHARPNESS_BOUNDS: [f32; 3] = [45.0, 90.0, 135.0];
+// Здоровье таргета; 0 - таргеты бессмертны
+pub const TARGET_HEALTH: f32 = 0.0;
+pub const TARGET_DAMAGE_PER_SECOND: f32 = 100.0;
+pub const FLICK_TRIALS: u32 = 20;
+pub const FLICK_MIN_ANGLE: f32 = 5.0;
+pub const FLICK_MAX_ANGLE: f32 = 30.0;
+pub const REACTION_TRIALS: u32 = 20;
+pub const REACTION_FOREPERIOD_MIN: f32 = 1.0;
+pub const REACTION_FOREPERIOD_MAX: f32 = 3.0;
+pub const REACTION_GO_PROBABILITY: f32 = 0.7;
+// Go/no-go: цвет таргета и два отвлекателя, повернутых по тону на 120°
+pub const REACTION_STIMULUS_COLORS: usize = 3;
+
 pub const DEFAULT_DPI: f32 = 1600.0;
 pub const DEFAULT_CM_360: f32 = 38.0;
 pub const DEFAULT_FOV: f32 = 103.0;
+
+pub const SCENARIOS_DIR: &str = "assets/scenarios";
+pub const RUNS_DIR: &str = "assets/runs";
+pub const HISTORY_PATH: &str = "assets/history.jsonl";
@@ -2,3 +2,7 @@ use bevy::prelude::*;
 
 #[derive(Event)]
 pub struct SaveConfigEvent;
+
+// Отправляется из update_target, когда тест завершен и проанализирован
+#[derive(Event)]
+pub struct TestFinishedEvent;
@@ -3,6 +3,7 @@ pub mod components;
 pub mod constants;
 pub mod events;
 pub mod resources;
+pub mod scenario;
 pub mod types;
 
 pub use cfgsettings::*;
@@ -10,4 +11,5 @@ pub use components::*;
 pub use constants::*;
 pub use events::*;
 pub use resources::*;
+pub use scenario::*;
 pub use types::*;
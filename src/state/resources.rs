@@ -1,7 +1,17 @@
 use crate::constants::*;
-use crate::{ColorTarget, DataPoint, InputField};
+use crate::stats;
+use crate::{
+    ColorTarget, DataPoint, DelayStats, DetectorReport, DirectionBreakdown, DirectionChange,
+    FlickSummary, FlickTrial, FlickTrialState, HeuristicDetectorConfig, InputField, Kill,
+    KillSummary, ReactionMatch, ReactionTrial, ReactionTrialState, ReactionTrialSummary,
+    RunSettings, Scenario, SizeBin, StaircaseState, StaircaseStep, StaircaseSummary, SwitchSummary,
+    TargetState, TargetSwitch, TestPause,
+};
 use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::f32::consts::PI;
+use std::time::Duration;
 
 // Ресурс для отслеживания статуса загрузки конфига
 #[derive(Resource)]
@@ -48,10 +58,27 @@ pub struct Settings {
     pub texture_file: String,
     pub font_file: String,
     pub hit_sound_file: String,
+    pub stimulus_sound_files: Vec<String>,
+    // Задержка аудиовыхода, мс
+    pub audio_output_latency: f32,
     pub key_restart: KeyCode,
     pub key_start: KeyCode,
     pub key_settings: KeyCode,
     pub key_fullscreen: KeyCode,
+    pub key_history: KeyCode,
+    pub key_replay: KeyCode,
+    pub key_ghost: KeyCode,
+    pub key_shoot: MouseButton,
+    pub scenario: String,
+    // 0 - новый случайный seed для каждого теста
+    pub seed: u64,
+    // Длительность теста, секунды; 0 - как в сценарии
+    pub test_duration: f32,
+    // Обратный отсчет 3-2-1 перед тестом, секунды; 0 - без отсчета
+    pub countdown: f32,
+    // Активный детектор реакций и параметры эвристики
+    pub detector: String,
+    pub heuristic_detector: HeuristicDetectorConfig,
     // UI состояние для color picker
     pub color_picker_open: bool,
     pub picker_hue: f32,
@@ -92,10 +119,22 @@ impl Default for Settings {
             texture_file: String::from("texture.png"),
             font_file: String::from("font.ttf"),
             hit_sound_file: String::from("hit.ogg"),
+            stimulus_sound_files: vec![String::from("hit.ogg")],
+            audio_output_latency: 0.0,
             key_restart: KeyCode::KeyR,
             key_start: KeyCode::Space,
             key_settings: KeyCode::Escape,
             key_fullscreen: KeyCode::F12,
+            key_history: KeyCode::KeyH,
+            key_replay: KeyCode::KeyP,
+            key_ghost: KeyCode::KeyG,
+            key_shoot: MouseButton::Left,
+            scenario: String::from("Default"),
+            seed: 0,
+            test_duration: 0.0,
+            countdown: COUNTDOWN_DURATION,
+            detector: String::from("heuristic"),
+            heuristic_detector: HeuristicDetectorConfig::default(),
             color_picker_open: false,
             picker_hue: 0.0,
             picker_saturation: 1.0,
@@ -106,6 +145,27 @@ impl Default for Settings {
 }
 
 impl Settings {
+    // Seed для следующего теста: фиксированный из настроек или случайный.
+    // Случайный не бывает 0: seed 0 в настройках значит "случайный", такой тест не повторить
+    pub fn next_run_seed(&self) -> u64 {
+        if self.seed != 0 {
+            self.seed
+        } else {
+            rand::random::<u32>().max(1) as u64
+        }
+    }
+
+    pub fn run_settings(&self) -> RunSettings {
+        RunSettings {
+            dpi: self.dpi,
+            cm_360: self.cm_360,
+            fov: self.fov,
+            audio_output_latency: self.audio_output_latency,
+            test_duration: self.test_duration,
+            countdown: self.countdown,
+        }
+    }
+
     pub fn mouse_sensitivity(&self) -> f32 {
         let inches_360 = self.cm_360 / 2.54;
         let dots_360 = inches_360 * self.dpi;
@@ -181,20 +241,24 @@ pub struct ReactionTest {
     pub data: Vec<DataPoint>,
     pub is_running: bool,
     pub start_time: f32,
+    // Время Time<Fixed> в момент старта, для точного отсчета тиков
+    pub fixed_start: Duration,
+    // На паузе тест стоит; paused_at - Time<Fixed> в момент паузы
+    pub paused: bool,
+    pub paused_at: Duration,
+    pub pauses: Vec<TestPause>,
     pub target_position: Vec3,
     pub crosshair_direction: Vec3,
+    // Позиция и скорость активного таргета (копия targets[active_target])
     pub target_velocity: Vec3,
     pub target_distance: f32,
     pub start_cam_pos: Vec3,
     pub start_cam_forward: Vec3,
-    pub next_direction_change: f32,
-    pub change_interval: f32,
     pub rms_distance: f32,
     pub peak_angular_error: f32,
     pub test_completed: bool,
     pub camera_yaw: f32,
     pub camera_pitch: f32,
-    pub last_direction_change_time: f32,
     pub hits: u32,
     pub misses: u32,
     pub last_shot_time: f32,
@@ -203,6 +267,74 @@ pub struct ReactionTest {
     pub react_directions: usize,
     pub median_delay: f32,
     pub average_delay: f32,
+    pub delay_stats: DelayStats,
+    // Оценка задержки по взаимной корреляции (мс) и посегментные оценки
+    pub xcorr_delay: f32,
+    pub xcorr_correlation: f32,
+    pub xcorr_segment_delays: Vec<f32>,
+    pub xcorr_segment_count: usize,
+    pub scenario: Scenario,
+    // Все случайные решения таргета идут через этот генератор
+    pub seed: u64,
+    pub rng: StdRng,
+    pub run_settings: RunSettings,
+    // Результаты анализа, сохраняются для экспорта
+    pub direction_changes: Vec<DirectionChange>,
+    pub reactions: Vec<ReactionMatch>,
+    pub detector_reports: Vec<DetectorReport>,
+    // Все таргеты; создаются на первом тике теста
+    pub targets: Vec<TargetState>,
+    pub active_target: usize,
+    pub next_target_switch: f32,
+    pub target_switches: Vec<TargetSwitch>,
+    pub switch_summary: SwitchSummary,
+    // Точность по угловому размеру таргета
+    pub size_bins: Vec<SizeBin>,
+    pub direction_breakdown: DirectionBreakdown,
+    // Адаптивная лестница: состояние, блоки и итог
+    pub staircase: Option<StaircaseState>,
+    pub staircase_steps: Vec<StaircaseStep>,
+    pub staircase_summary: StaircaseSummary,
+    // Режим со здоровьем: нанесенный урон, убийства и итог
+    pub damage_dealt: f32,
+    pub kills: Vec<Kill>,
+    pub kill_summary: KillSummary,
+    // Flick-режим: текущая попытка, завершенные попытки и итог
+    pub flick_trial: Option<FlickTrialState>,
+    pub flick_trials: Vec<FlickTrial>,
+    pub flick_summary: FlickSummary,
+    // Простая реакция: текущая попытка, завершенные попытки и итог
+    pub reaction_trial: Option<ReactionTrialState>,
+    pub reaction_trials: Vec<ReactionTrial>,
+    pub reaction_summary: ReactionTrialSummary,
+}
+
+// Сценарии, найденные в assets/scenarios при запуске
+#[derive(Resource, Default)]
+pub struct ScenarioLibrary {
+    pub scenarios: Vec<Scenario>,
+}
+
+impl ScenarioLibrary {
+    pub fn get(&self, name: &str) -> Scenario {
+        self.scenarios
+            .iter()
+            .find(|s| s.name == name)
+            .or_else(|| self.scenarios.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn next_name(&self, current: &str) -> Option<String> {
+        if self.scenarios.is_empty() {
+            return None;
+        }
+        let next = match self.scenarios.iter().position(|s| s.name == current) {
+            Some(i) => (i + 1) % self.scenarios.len(),
+            None => 0,
+        };
+        Some(self.scenarios[next].name.clone())
+    }
 }
 
 #[derive(Resource)]
@@ -213,6 +345,15 @@ pub struct GameState {
 #[derive(Resource)]
 pub struct GameAudio {
     pub hit_sound: Handle<AudioSource>,
+    // Звуки стимула слуховой реакции (из stimulus_sound_files)
+    pub stimulus_sounds: Vec<Handle<AudioSource>>,
+}
+
+impl ReactionTest {
+    // Доля попаданий автоматической стрельбы, %
+    pub fn accuracy(&self) -> f32 {
+        stats::percent(self.hits as usize, (self.hits + self.misses) as usize)
+    }
 }
 
 impl Default for ReactionTest {
@@ -221,28 +362,60 @@ impl Default for ReactionTest {
             data: Vec::new(),
             is_running: false,
             start_time: 0.0,
+            fixed_start: Duration::ZERO,
+            paused: false,
+            paused_at: Duration::ZERO,
+            pauses: Vec::new(),
             target_position: Vec3::ZERO,
             crosshair_direction: Vec3::NEG_Z,
             target_velocity: Vec3::new(1.0, 1.0, 1.0).normalize() * TARGET_SPEED,
             target_distance: 15.0,
             start_cam_pos: Vec3::ZERO,
             start_cam_forward: Vec3::NEG_Z,
-            next_direction_change: 0.1,
-            change_interval: 0.1,
             average_delay: 0.0,
             rms_distance: 0.0,
             peak_angular_error: 0.0,
             test_completed: false,
             camera_yaw: 0.0,
             camera_pitch: 0.0,
-            last_direction_change_time: 0.0,
             hits: 0,
             misses: 0,
             last_shot_time: 0.0,
             shot_interval: 1.0 / 30.0,
             median_delay: 0.0,
+            delay_stats: DelayStats::default(),
             count_directions: 0,
             react_directions: 0,
+            xcorr_delay: 0.0,
+            xcorr_correlation: 0.0,
+            xcorr_segment_delays: Vec::new(),
+            xcorr_segment_count: 0,
+            scenario: Scenario::default(),
+            seed: 0,
+            rng: StdRng::seed_from_u64(0),
+            run_settings: RunSettings::default(),
+            direction_changes: Vec::new(),
+            reactions: Vec::new(),
+            detector_reports: Vec::new(),
+            targets: Vec::new(),
+            active_target: 0,
+            next_target_switch: 0.0,
+            target_switches: Vec::new(),
+            switch_summary: SwitchSummary::default(),
+            size_bins: Vec::new(),
+            direction_breakdown: DirectionBreakdown::default(),
+            staircase: None,
+            staircase_steps: Vec::new(),
+            staircase_summary: StaircaseSummary::default(),
+            damage_dealt: 0.0,
+            kills: Vec::new(),
+            kill_summary: KillSummary::default(),
+            flick_trial: None,
+            flick_trials: Vec::new(),
+            flick_summary: FlickSummary::default(),
+            reaction_trial: None,
+            reaction_trials: Vec::new(),
+            reaction_summary: ReactionTrialSummary::default(),
         }
     }
 }
@@ -283,3 +456,112 @@ impl Default for FresnelTracker {
         }
     }
 }
+
+// Просмотр записанного теста: позиция на таймлайне, скорость и сама запись
+#[derive(Resource)]
+pub struct ReplayViewer {
+    pub active: bool,
+    pub playing: bool,
+    // Время записи, секунды
+    pub time: f32,
+    pub speed: f32,
+    pub duration: f32,
+    pub title: String,
+    pub data: Vec<DataPoint>,
+    pub direction_changes: Vec<DirectionChange>,
+    pub reactions: Vec<ReactionMatch>,
+    // Скачки трассы (смены таргета, респауны): след через них не тянется
+    pub trace_jumps: Vec<f32>,
+}
+
+impl Default for ReplayViewer {
+    fn default() -> Self {
+        Self {
+            active: false,
+            playing: false,
+            time: 0.0,
+            speed: 1.0,
+            duration: 0.0,
+            title: String::new(),
+            data: Vec::new(),
+            direction_changes: Vec::new(),
+            reactions: Vec::new(),
+            trace_jumps: Vec::new(),
+        }
+    }
+}
+
+impl ReplayViewer {
+    pub fn open(
+        title: String,
+        data: Vec<DataPoint>,
+        direction_changes: Vec<DirectionChange>,
+        reactions: Vec<ReactionMatch>,
+        trace_jumps: Vec<f32>,
+    ) -> Self {
+        Self {
+            active: true,
+            playing: true,
+            duration: data.last().map_or(0.0, |p| p.time),
+            title,
+            data,
+            direction_changes,
+            reactions,
+            trace_jumps,
+            ..default()
+        }
+    }
+
+    // Тик записи, ближайший к текущему времени (не раньше его)
+    pub fn index_at(&self, time: f32) -> usize {
+        self.data
+            .partition_point(|p| p.time < time)
+            .min(self.data.len().saturating_sub(1))
+    }
+}
+
+// Гонка с призраком: записанный тест, с которым сравнивается текущий.
+// Тест идет по сценарию и seed записи, поэтому путь таргета тот же
+#[derive(Resource, Default)]
+pub struct GhostRace {
+    pub title: String,
+    pub scenario: Scenario,
+    pub seed: u64,
+    pub data: Vec<DataPoint>,
+    // Текущий тест запущен против призрака
+    pub racing: bool,
+    // Время на таргете к текущему моменту теста, секунды, и сколько тиков уже просмотрено
+    pub live_on_target: f32,
+    pub ghost_on_target: f32,
+    pub live_index: usize,
+    pub ghost_index: usize,
+}
+
+impl GhostRace {
+    pub fn new(title: String, scenario: Scenario, seed: u64, data: Vec<DataPoint>) -> Self {
+        Self {
+            title,
+            scenario,
+            seed,
+            data,
+            ..default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.data.is_empty()
+    }
+
+    pub fn start_race(&mut self) {
+        self.racing = true;
+        self.live_on_target = 0.0;
+        self.ghost_on_target = 0.0;
+        self.live_index = 0;
+        self.ghost_index = 0;
+    }
+
+    // Отрыв от призрака по времени на таргете; плюс - игрок впереди
+    pub fn lead(&self) -> f32 {
+        self.live_on_target - self.ghost_on_target
+    }
+}
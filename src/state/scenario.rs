@@ -0,0 +1,256 @@
+use crate::constants::*;
+use serde::{Deserialize, Serialize};
+
+// Что измеряет сценарий
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TestMode {
+    // Слежение за движущимся таргетом, автоматическая стрельба
+    #[default]
+    Tracking,
+    // Статичные таргеты на случайном угловом расстоянии, выстрел по клику
+    Flick,
+    // Простая зрительная реакция: таргет меняет цвет после случайной паузы, игрок кликает
+    Reaction,
+    // Реакция выбора: цвет таргета - левый клик, другой цвет - правый
+    Choice,
+    // Go/no-go: кликать только на цвет таргета, цвета-отвлекатели пропускать
+    GoNoGo,
+}
+
+impl TestMode {
+    // Режимы с попытками "пауза - стимул - клик"
+    pub fn is_reaction(self) -> bool {
+        matches!(
+            self,
+            TestMode::Reaction | TestMode::Choice | TestMode::GoNoGo
+        )
+    }
+}
+
+// Стимул простой реакции
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StimulusKind {
+    // Таргет меняет цвет
+    #[default]
+    Visual,
+    // Звучит звук из stimulus_sound_files
+    Auditory,
+    // Каждая попытка случайно зрительная или слуховая
+    Mixed,
+}
+
+// Что меняет адаптивная лестница
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StaircaseParameter {
+    // Скорость таргета, м/с
+    #[default]
+    Speed,
+    // Радиус таргета, м
+    Size,
+    // Средний интервал между сменами направления, секунды
+    ChangeInterval,
+}
+
+// Адаптивная сложность: после down успешных блоков подряд сложнее, после up неудачных - проще
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct StaircaseConfig {
+    pub parameter: StaircaseParameter,
+    // Длительность блока, секунды
+    pub block_duration: f32,
+    // Блок успешен, если прицел был на таргете не меньше этой доли времени
+    pub on_target_threshold: f32,
+    // Шаг уровня сложности, доля (уровень умножается или делится на 1 + step)
+    pub step: f32,
+    pub up: u32,
+    pub down: u32,
+    // Порог - среднее по последним разворотам
+    pub reversals: usize,
+    // Пределы уровня сложности относительно значения из сценария
+    pub min_level: f32,
+    pub max_level: f32,
+}
+
+impl Default for StaircaseConfig {
+    fn default() -> Self {
+        Self {
+            parameter: StaircaseParameter::Speed,
+            block_duration: 2.0,
+            on_target_threshold: 0.5,
+            step: 0.1,
+            up: 1,
+            down: 2,
+            reversals: 6,
+            min_level: 0.2,
+            max_level: 5.0,
+        }
+    }
+}
+
+// Модель движения таргета и ее параметры (поле "type" в JSON)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MotionConfig {
+    // Постоянная скорость, резкий поворот раз в change_interval (параметры сценария)
+    #[default]
+    RandomTurn,
+    Sinusoidal(SinusoidalMotion),
+    Adad(AdadMotion),
+    JerkLimited(JerkLimitedMotion),
+}
+
+// Синусоидальный стрейф вокруг стартовой точки
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SinusoidalMotion {
+    // Амплитуда, метры, и период, секунды, по горизонтали и вертикали
+    pub amplitude_x: f32,
+    pub period_x: f32,
+    pub amplitude_y: f32,
+    pub period_y: f32,
+}
+
+impl Default for SinusoidalMotion {
+    fn default() -> Self {
+        Self {
+            amplitude_x: 4.0,
+            period_x: 2.0,
+            amplitude_y: 0.0,
+            period_y: 3.0,
+        }
+    }
+}
+
+// Стрейф влево-вправо (A-D) с разгоном и торможением до target_speed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AdadMotion {
+    // Ускорение, м/с^2
+    pub acceleration: f32,
+    // Длительность одного стрейфа, секунды
+    pub strafe_min: f32,
+    pub strafe_max: f32,
+}
+
+impl Default for AdadMotion {
+    fn default() -> Self {
+        Self {
+            acceleration: 40.0,
+            strafe_min: 0.15,
+            strafe_max: 0.6,
+        }
+    }
+}
+
+// Плавные кривые: ограничены и ускорение, и его производная (рывок)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct JerkLimitedMotion {
+    // м/с^2 и м/с^3
+    pub max_acceleration: f32,
+    pub max_jerk: f32,
+    // Как часто выбирается новое желаемое направление, секунды
+    pub change_interval_min: f32,
+    pub change_interval_max: f32,
+}
+
+impl Default for JerkLimitedMotion {
+    fn default() -> Self {
+        Self {
+            max_acceleration: 30.0,
+            max_jerk: 300.0,
+            change_interval_min: 0.3,
+            change_interval_max: 0.8,
+        }
+    }
+}
+
+// Описание одного тренировочного сценария (assets/scenarios/*.json)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Scenario {
+    pub name: String,
+    pub description: String,
+    pub mode: TestMode,
+    pub motion: MotionConfig,
+    pub target_size: f32,
+    pub target_speed: f32,
+    pub test_duration: f32,
+    // Данные первых warmup секунд записываются, но не анализируются
+    pub warmup: f32,
+    pub target_bound_x: f32,
+    pub target_bound_y: f32,
+    pub target_bound_z_min: f32,
+    pub target_bound_z_max: f32,
+    // Интервал между сменами направления, секунды
+    pub change_interval_min: f32,
+    pub change_interval_max: f32,
+    // Минимальный угол поворота при смене направления, градусы
+    pub min_turn_angle: f32,
+    // Скорость движения по глубине (к камере и от нее), м/с; 0 - только вбок
+    pub depth_speed: f32,
+    // Период качания дистанции от target_bound_z_min до max и обратно, секунды; 0 - выкл.
+    pub distance_sweep_period: f32,
+    // Здоровье таргета и урон в секунду, пока прицел на нем; 0 - без здоровья
+    pub target_health: f32,
+    pub damage_per_second: f32,
+    // Несколько таргетов: активный подсвечивается и меняется раз в switch_interval секунд
+    pub target_count: usize,
+    pub switch_interval_min: f32,
+    pub switch_interval_max: f32,
+    // Flick: число попыток и диапазон углового расстояния до таргета, градусы
+    pub flick_trials: u32,
+    pub flick_min_angle: f32,
+    pub flick_max_angle: f32,
+    // Простая реакция: число попыток и диапазон паузы перед стимулом, секунды
+    pub reaction_trials: u32,
+    pub foreperiod_min: f32,
+    pub foreperiod_max: f32,
+    // Go/no-go: вероятность стимула цвета таргета
+    pub go_probability: f32,
+    // Простая реакция: зрительный, слуховой или смешанный стимул
+    pub stimulus: StimulusKind,
+    // Адаптивная сложность в режиме слежения; None - сложность постоянная
+    pub staircase: Option<StaircaseConfig>,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_string(),
+            description: "Random turns every 0.2-0.5 s".to_string(),
+            mode: TestMode::Tracking,
+            motion: MotionConfig::RandomTurn,
+            target_size: TARGET_SIZE,
+            target_speed: TARGET_SPEED,
+            test_duration: TEST_DURATION,
+            warmup: WARMUP_DURATION,
+            target_bound_x: TARGET_BOUND_X,
+            target_bound_y: TARGET_BOUND_Y,
+            target_bound_z_min: TARGET_BOUND_Z_MIN,
+            target_bound_z_max: TARGET_BOUND_Z_MAX,
+            change_interval_min: TARGET_CHANGE_INTERVAL_MIN,
+            change_interval_max: TARGET_CHANGE_INTERVAL_MAX,
+            min_turn_angle: TARGET_MIN_TURN_ANGLE,
+            depth_speed: TARGET_DEPTH_SPEED,
+            distance_sweep_period: DISTANCE_SWEEP_PERIOD,
+            target_health: TARGET_HEALTH,
+            damage_per_second: TARGET_DAMAGE_PER_SECOND,
+            target_count: TARGET_COUNT,
+            switch_interval_min: TARGET_SWITCH_INTERVAL_MIN,
+            switch_interval_max: TARGET_SWITCH_INTERVAL_MAX,
+            flick_trials: FLICK_TRIALS,
+            flick_min_angle: FLICK_MIN_ANGLE,
+            flick_max_angle: FLICK_MAX_ANGLE,
+            reaction_trials: REACTION_TRIALS,
+            foreperiod_min: REACTION_FOREPERIOD_MIN,
+            foreperiod_max: REACTION_FOREPERIOD_MAX,
+            go_probability: REACTION_GO_PROBABILITY,
+            stimulus: StimulusKind::Visual,
+            staircase: None,
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use crate::constants::MIN_DETECTION_RATE;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum AppState {
@@ -6,6 +8,7 @@ pub enum AppState {
     Loading,
     Settings,
     Game,
+    Results,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,6 +20,9 @@ pub enum InputField {
     FresnelPower,
     DirectionalLight,
     AmbientLight,
+    Seed,
+    TestDuration,
+    Countdown,
 }
 
 #[derive(Clone, Copy)]
@@ -26,7 +32,7 @@ pub enum ColorTarget {
     Fresnel,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DataPoint {
     pub time: f32,
     pub target_pos: Vec3,
@@ -39,8 +45,329 @@ pub struct DataPoint {
     pub crosshair_y: f32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DirectionChange {
     pub time: f32,
+    // Нет в старых записях: тогда резкость поворота неизвестна
+    #[serde(default)]
+    pub target_old_direction: Vec2,
     pub target_new_direction: Vec2,
 }
+
+// Смена направления таргета и найденная реакция игрока на нее
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReactionMatch {
+    pub change_time: f32,
+    pub reaction_time: f32,
+    pub delay: f32,
+}
+
+// Состояние движения одного таргета
+#[derive(Clone, Debug)]
+pub struct TargetState {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub next_direction_change: f32,
+    pub change_interval: f32,
+    pub last_direction_change_time: f32,
+    // Состояние моделей движения: центр и фаза синусоиды, ускорение, желаемая скорость
+    pub origin: Vec3,
+    pub phase: f32,
+    pub acceleration: Vec3,
+    pub desired_velocity: Vec3,
+    // Оставшееся здоровье и время появления (для time-to-kill)
+    pub health: f32,
+    pub spawn_time: f32,
+}
+
+impl TargetState {
+    pub fn new(position: Vec3, velocity: Vec3) -> Self {
+        Self {
+            position,
+            velocity,
+            next_direction_change: 0.1,
+            change_interval: 0.1,
+            last_direction_change_time: 0.0,
+            origin: position,
+            phase: 0.0,
+            acceleration: Vec3::ZERO,
+            desired_velocity: velocity,
+            health: 0.0,
+            spawn_time: 0.0,
+        }
+    }
+}
+
+// Смена активного таргета; switch_time - сколько секунд прицел шел к новому таргету
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TargetSwitch {
+    pub time: f32,
+    pub from: usize,
+    pub to: usize,
+    // None - прицел так и не навелся на новый таргет
+    pub switch_time: Option<f32>,
+}
+
+// Итог переключений между таргетами (время в мс)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SwitchSummary {
+    pub switches: usize,
+    pub acquired: usize,
+    pub average_switch_time: f32,
+    pub median_switch_time: f32,
+}
+
+// Разброс задержек активного детектора (мс) и доля смен направления с реакцией
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DelayStats {
+    pub samples: usize,
+    pub std_dev: f32,
+    pub min: f32,
+    pub max: f32,
+    pub p10: f32,
+    pub p90: f32,
+    // 95% бутстреп-интервал средней задержки
+    pub ci_low: f32,
+    pub ci_high: f32,
+    pub detection_rate: f32,
+}
+
+impl DelayStats {
+    pub fn low_detection_rate(&self) -> bool {
+        self.detection_rate < MIN_DETECTION_RATE
+    }
+}
+
+// Пауза посреди теста; time - время теста, duration - длительность паузы, секунды
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TestPause {
+    pub time: f32,
+    pub duration: f32,
+}
+
+// Убийство таргета; time_to_kill - секунды от появления таргета
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Kill {
+    pub time: f32,
+    pub target: usize,
+    pub time_to_kill: f32,
+}
+
+// Итог режима со здоровьем (время в мс)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KillSummary {
+    pub kills: usize,
+    pub average_time_to_kill: f32,
+    pub median_time_to_kill: f32,
+    pub damage_dealt: f32,
+    // Нанесенный урон от максимально возможного за тест
+    pub damage_efficiency: f32,
+}
+
+// Один блок адаптивной лестницы
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StaircaseStep {
+    // Конец блока, секунды от начала теста
+    pub time: f32,
+    // Значение параметра в течение блока
+    pub value: f32,
+    // Доля времени на таргете
+    pub on_target: f32,
+    // Изменение сложности после блока: 1 - сложнее, -1 - проще, 0 - без изменений
+    pub change: i8,
+}
+
+// Текущее состояние лестницы; значения параметра считаются от level и сценария
+#[derive(Clone, Debug)]
+pub struct StaircaseState {
+    pub level: f32,
+    pub block_start: f32,
+    pub ticks: u32,
+    pub on_target_ticks: u32,
+    pub successes: u32,
+    pub failures: u32,
+}
+
+// Итог лестницы: порог - среднее значение параметра в точках разворота
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StaircaseSummary {
+    pub parameter: String,
+    pub threshold: f32,
+    pub reversals: usize,
+    pub blocks: usize,
+    pub start_value: f32,
+    pub final_value: f32,
+}
+
+// Точность на отрезке угловых размеров таргета, градусы
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SizeBin {
+    pub min_size: f32,
+    pub max_size: f32,
+    // Доля тиков теста в этом отрезке
+    pub time_share: f32,
+    pub average_error: f32,
+    // Доля тиков, когда прицел был на таргете
+    pub on_target: f32,
+}
+
+// Реакции и точность по группе смен направления (время в мс, ошибка в градусах)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DirectionBin {
+    pub label: String,
+    pub changes: usize,
+    pub reactions: usize,
+    pub average_delay: f32,
+    pub median_delay: f32,
+    // Средняя угловая ошибка от смены направления до следующей
+    pub average_error: f32,
+}
+
+// Разбивка по направлению движения после поворота, по оси разворота и по резкости поворота
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DirectionBreakdown {
+    pub by_direction: Vec<DirectionBin>,
+    pub by_axis: Vec<DirectionBin>,
+    pub by_turn: Vec<DirectionBin>,
+}
+
+// Одна попытка flick-режима (углы в градусах, время в секундах от появления таргета)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FlickTrial {
+    pub start_time: f32,
+    // Угловое расстояние от прицела до таргета в момент появления
+    pub distance: f32,
+    // Угловой размер таргета
+    pub target_width: f32,
+    pub time_to_first_move: Option<f32>,
+    // None - таргет не был сбит за отведенное время
+    pub time_to_hit: Option<f32>,
+    // Насколько прицел проскочил таргет вдоль линии флика
+    pub overshoot: f32,
+    pub misses: u32,
+}
+
+// Текущая попытка flick-режима
+#[derive(Clone, Debug)]
+pub struct FlickTrialState {
+    pub start_time: f32,
+    // Углы (yaw, pitch) прицела в момент появления и таргета, радианы
+    pub start_angles: Vec2,
+    pub target_angles: Vec2,
+    pub first_move: Option<f32>,
+    // Максимальное продвижение прицела вдоль линии флика, радианы
+    pub max_progress: f32,
+    pub misses: u32,
+}
+
+// Итог flick-режима (время в мс)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FlickSummary {
+    pub trials: usize,
+    pub hits: usize,
+    pub average_first_move: f32,
+    pub average_time_to_hit: f32,
+    pub average_overshoot: f32,
+    // Закон Фиттса: время = a + b * ID, ID = log2(D / W + 1)
+    pub fitts_correlation: f32,
+    pub fitts_intercept: f32,
+    pub fitts_slope: f32,
+}
+
+// Одна попытка режимов реакции (секунды от начала теста / от стимула)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReactionTrial {
+    pub start_time: f32,
+    // Пауза от начала попытки до смены цвета
+    pub foreperiod: f32,
+    // Только для правильных ответов
+    pub reaction_time: Option<f32>,
+    // Клик до появления стимула
+    pub false_start: bool,
+    // 0 - цвет таргета, иначе номер цвета-отвлекателя
+    #[serde(default)]
+    pub stimulus: usize,
+    // Ошибка действия: клик на no-go стимул или не той кнопкой
+    #[serde(default)]
+    pub commission: bool,
+    // Ошибка пропуска: нет клика на стимул, требующий ответа
+    #[serde(default)]
+    pub omission: bool,
+    // Слуховой стимул и задержка старта звука в движке (до AudioSink), секунды
+    #[serde(default)]
+    pub auditory: bool,
+    #[serde(default, alias = "audio_latency")]
+    pub sound_start_delay: Option<f32>,
+}
+
+// Текущая попытка режимов реакции
+#[derive(Clone, Debug)]
+pub struct ReactionTrialState {
+    pub start_time: f32,
+    pub foreperiod: f32,
+    // Таргет уже сменил цвет
+    pub stimulus_shown: bool,
+    pub stimulus: usize,
+    pub auditory: bool,
+    pub sound_start_delay: Option<f32>,
+}
+
+impl ReactionTrialState {
+    pub fn stimulus_time(&self) -> f32 {
+        self.start_time + self.foreperiod
+    }
+}
+
+// Итог режимов реакции (время в мс)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReactionTrialSummary {
+    pub trials: usize,
+    pub responses: usize,
+    pub false_starts: usize,
+    #[serde(default)]
+    pub commission_errors: usize,
+    #[serde(default)]
+    pub omission_errors: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub sd: f32,
+    // Сравнение слуховых и зрительных попыток. audio_latency - вычитаемая поправка:
+    // измеренная задержка старта звука плюс audio_output_latency из настроек
+    #[serde(default)]
+    pub visual_mean: f32,
+    #[serde(default)]
+    pub auditory_mean: f32,
+    #[serde(default)]
+    pub sound_start_delay: f32,
+    #[serde(default)]
+    pub audio_latency: f32,
+    #[serde(default)]
+    pub auditory_corrected_mean: f32,
+}
+
+// Итог одного детектора реакций (для сравнения детекторов)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DetectorReport {
+    pub name: String,
+    pub average_delay: f32,
+    pub median_delay: f32,
+    pub count_directions: usize,
+    pub react_directions: usize,
+}
+
+// Настройки мыши и камеры, с которыми был пройден тест
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct RunSettings {
+    pub dpi: f32,
+    pub cm_360: f32,
+    pub fov: f32,
+    // Задержка аудиовыхода, мс
+    #[serde(default)]
+    pub audio_output_latency: f32,
+    // Длительность из настроек, секунды; 0 - из сценария
+    #[serde(default)]
+    pub test_duration: f32,
+    // Обратный отсчет перед тестом, секунды
+    #[serde(default)]
+    pub countdown: f32,
+}
@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::replay_file::{load_replay, save_replay, REPLAY_EXTENSION};
+use crate::state::*;
+
+pub const RUN_EXPORT_VERSION: u32 = 1;
+
+// Итоговые метрики теста
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RunSummary {
+    pub average_delay: f32,
+    pub median_delay: f32,
+    pub count_directions: usize,
+    pub react_directions: usize,
+    #[serde(default)]
+    pub delay_stats: DelayStats,
+    pub hits: u32,
+    pub misses: u32,
+    pub rms_distance: f32,
+    pub peak_angular_error: f32,
+    #[serde(default)]
+    pub xcorr_delay: f32,
+    #[serde(default)]
+    pub xcorr_correlation: f32,
+    #[serde(default)]
+    pub flick: FlickSummary,
+    #[serde(default)]
+    pub switching: SwitchSummary,
+    #[serde(default)]
+    pub size_bins: Vec<SizeBin>,
+    #[serde(default)]
+    pub directions: DirectionBreakdown,
+    #[serde(default)]
+    pub kills: KillSummary,
+    #[serde(default)]
+    pub reaction: ReactionTrialSummary,
+    #[serde(default)]
+    pub staircase: StaircaseSummary,
+}
+
+impl From<&ReactionTest> for RunSummary {
+    fn from(test: &ReactionTest) -> Self {
+        Self {
+            average_delay: test.average_delay,
+            median_delay: test.median_delay,
+            count_directions: test.count_directions,
+            react_directions: test.react_directions,
+            delay_stats: test.delay_stats.clone(),
+            hits: test.hits,
+            misses: test.misses,
+            rms_distance: test.rms_distance,
+            peak_angular_error: test.peak_angular_error,
+            xcorr_delay: test.xcorr_delay,
+            xcorr_correlation: test.xcorr_correlation,
+            flick: test.flick_summary.clone(),
+            switching: test.switch_summary.clone(),
+            size_bins: test.size_bins.clone(),
+            directions: test.direction_breakdown.clone(),
+            kills: test.kill_summary.clone(),
+            reaction: test.reaction_summary.clone(),
+            staircase: test.staircase_summary.clone(),
+        }
+    }
+}
+
+// Полная телеметрия одного теста (assets/runs/run_*.json)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunExport {
+    pub version: u32,
+    // Unix-время завершения теста, секунды
+    pub timestamp: u64,
+    pub scenario: Scenario,
+    pub seed: u64,
+    pub settings: RunSettings,
+    pub summary: RunSummary,
+    pub direction_changes: Vec<DirectionChange>,
+    pub reactions: Vec<ReactionMatch>,
+    #[serde(default)]
+    pub flick_trials: Vec<FlickTrial>,
+    #[serde(default)]
+    pub target_switches: Vec<TargetSwitch>,
+    #[serde(default)]
+    pub kills: Vec<Kill>,
+    #[serde(default)]
+    pub reaction_trials: Vec<ReactionTrial>,
+    #[serde(default)]
+    pub staircase_steps: Vec<StaircaseStep>,
+    #[serde(default)]
+    pub pauses: Vec<TestPause>,
+    pub data: Vec<DataPoint>,
+}
+
+impl RunExport {
+    pub fn from_test(test: &ReactionTest, timestamp: u64) -> Self {
+        Self {
+            version: RUN_EXPORT_VERSION,
+            timestamp,
+            scenario: test.scenario.clone(),
+            seed: test.seed,
+            settings: test.run_settings,
+            summary: RunSummary::from(test),
+            direction_changes: test.direction_changes.clone(),
+            reactions: test.reactions.clone(),
+            flick_trials: test.flick_trials.clone(),
+            target_switches: test.target_switches.clone(),
+            kills: test.kills.clone(),
+            reaction_trials: test.reaction_trials.clone(),
+            staircase_steps: test.staircase_steps.clone(),
+            pauses: test.pauses.clone(),
+            data: test.data.clone(),
+        }
+    }
+
+    // Восстанавливает завершенный тест для повторного анализа
+    pub fn to_test(&self) -> ReactionTest {
+        ReactionTest {
+            scenario: self.scenario.clone(),
+            seed: self.seed,
+            run_settings: self.settings,
+            hits: self.summary.hits,
+            misses: self.summary.misses,
+            data: self.data.clone(),
+            flick_trials: self.flick_trials.clone(),
+            target_switches: self.target_switches.clone(),
+            kills: self.kills.clone(),
+            reaction_trials: self.reaction_trials.clone(),
+            staircase_steps: self.staircase_steps.clone(),
+            pauses: self.pauses.clone(),
+            damage_dealt: self.summary.kills.damage_dealt,
+            test_completed: true,
+            ..default()
+        }
+    }
+}
+
+// Система для сохранения телеметрии каждого завершенного теста
+pub fn export_finished_run(
+    mut finished_events: EventReader<TestFinishedEvent>,
+    test: Res<ReactionTest>,
+    settings: Res<Settings>,
+) {
+    for _event in finished_events.read() {
+        if test.data.is_empty() {
+            continue;
+        }
+        let export = RunExport::from_test(&test, unix_timestamp());
+        match save_run_export(&export, &GameConfig::from(&*settings), RUNS_DIR) {
+            Ok(paths) => {
+                for path in paths {
+                    info!("Run telemetry saved to {}", path.display());
+                }
+            }
+            Err(e) => {
+                error!("Failed to export run telemetry: {}", e);
+            }
+        }
+    }
+}
+
+// Сохраняет JSON, CSV и запись .mvrt с одинаковым именем, возвращает пути к файлам
+pub fn save_run_export(
+    export: &RunExport,
+    config: &GameConfig,
+    dir: &str,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)?;
+
+    let stem = free_stem(dir, &format!("run_{}", format_timestamp(export.timestamp)));
+    let json_path = Path::new(dir).join(format!("{}.json", stem));
+    let csv_path = Path::new(dir).join(format!("{}.csv", stem));
+    let replay_path = Path::new(dir).join(format!("{}.{}", stem, REPLAY_EXTENSION));
+
+    fs::write(&json_path, serde_json::to_string(export)?)?;
+    fs::write(&csv_path, run_export_to_csv(export))?;
+    save_replay(&replay_path, export, config)?;
+
+    Ok(vec![json_path, csv_path, replay_path])
+}
+
+// Имя с точностью до секунды: два теста за одну секунду получают суффикс _2, _3, ...
+fn free_stem(dir: &str, base: &str) -> String {
+    let taken = |stem: &str| {
+        ["json", "csv", REPLAY_EXTENSION]
+            .iter()
+            .any(|ext| Path::new(dir).join(format!("{}.{}", stem, ext)).exists())
+    };
+    let mut stem = base.to_string();
+    let mut index = 1;
+    while taken(&stem) {
+        index += 1;
+        stem = format!("{}_{}", base, index);
+    }
+    stem
+}
+
+// Читает и JSON, и запись .mvrt (по расширению)
+pub fn load_run_export(path: &Path) -> Result<RunExport, Box<dyn std::error::Error>> {
+    Ok(load_run_with_config(path)?.0)
+}
+
+// Тест и настройки игры, с которыми он прошел; настройки есть только в записях .mvrt
+pub fn load_run_with_config(
+    path: &Path,
+) -> Result<(RunExport, Option<GameConfig>), Box<dyn std::error::Error>> {
+    if path.extension().is_some_and(|ext| ext == REPLAY_EXTENSION) {
+        let replay = load_replay(path)?;
+        return Ok((replay.run, replay.config));
+    }
+    let content = fs::read_to_string(path)?;
+    let export: RunExport = serde_json::from_str(&content)?;
+    if export.version > RUN_EXPORT_VERSION {
+        return Err(format!(
+            "unsupported run export version {} (max {})",
+            export.version, RUN_EXPORT_VERSION
+        )
+        .into());
+    }
+    Ok((export, None))
+}
+
+// Один тик на строку; метаданные в строках-комментариях "#"
+pub fn run_export_to_csv(export: &RunExport) -> String {
+    let mut csv = String::new();
+    let _ = writeln!(csv, "# scenario: {}", export.scenario.name);
+    let _ = writeln!(csv, "# seed: {}", export.seed);
+    let _ = writeln!(csv, "# mode: {:?}", export.scenario.mode);
+    let _ = writeln!(csv, "# dpi: {}", export.settings.dpi);
+    let _ = writeln!(csv, "# cm_360: {}", export.settings.cm_360);
+    let _ = writeln!(csv, "# fov: {}", export.settings.fov);
+    let _ = writeln!(csv, "# average_delay_ms: {}", export.summary.average_delay);
+    let _ = writeln!(csv, "# median_delay_ms: {}", export.summary.median_delay);
+    let delays = &export.summary.delay_stats;
+    let _ = writeln!(csv, "# delay_sd_ms: {}", delays.std_dev);
+    let _ = writeln!(csv, "# delay_ci95_ms: {}-{}", delays.ci_low, delays.ci_high);
+    let _ = writeln!(csv, "# detection_rate: {}", delays.detection_rate);
+    let _ = writeln!(csv, "# xcorr_delay_ms: {}", export.summary.xcorr_delay);
+    let _ = writeln!(
+        csv,
+        "time,target_pos_x,target_pos_y,target_pos_z,crosshair_dir_x,crosshair_dir_y,crosshair_dir_z,\
+camera_pos_x,camera_pos_y,camera_pos_z,target_x,target_y,crosshair_x,crosshair_y,\
+direction_change,reaction,reaction_delay"
+    );
+
+    // Смены направления и реакции помечаются на ближайшем тике
+    let mut changes = export.direction_changes.iter().peekable();
+    let mut reactions = export.reactions.iter().peekable();
+
+    for point in &export.data {
+        let mut direction_change = 0;
+        while changes.peek().is_some_and(|c| c.time <= point.time) {
+            changes.next();
+            direction_change = 1;
+        }
+        let mut reaction_delay = None;
+        while reactions
+            .peek()
+            .is_some_and(|r| r.reaction_time <= point.time)
+        {
+            reaction_delay = reactions.next().map(|r| r.delay);
+        }
+
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            point.time,
+            point.target_pos.x,
+            point.target_pos.y,
+            point.target_pos.z,
+            point.crosshair_dir.x,
+            point.crosshair_dir.y,
+            point.crosshair_dir.z,
+            point.camera_pos.x,
+            point.camera_pos.y,
+            point.camera_pos.z,
+            point.target_x,
+            point.target_y,
+            point.crosshair_x,
+            point.crosshair_y,
+            direction_change,
+            reaction_delay.is_some() as u8,
+            reaction_delay.map(|d| d.to_string()).unwrap_or_default(),
+        );
+    }
+
+    csv
+}
+
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Unix-время -> "YYYY-MM-DD_HH-MM-SS" (UTC) без сторонних зависимостей
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let secs_of_day = timestamp % 86_400;
+
+    // Алгоритм civil_from_days (Howard Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60
+    )
+}
@@ -0,0 +1,315 @@
+use bevy::prelude::*;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use super::export::{format_timestamp, unix_timestamp};
+use crate::state::*;
+use crate::stats;
+
+// Сколько последних тестов входит в скользящее среднее
+pub const HISTORY_ROLLING_WINDOW: usize = 10;
+
+// Одна строка assets/history.jsonl. Задержки и ошибки прицела есть только у слежения;
+// у flick и режимов реакции основное время - trial_time, accuracy - доля удачных попыток
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    pub scenario: String,
+    pub seed: u64,
+    // Записи до появления режимов - тесты слежения
+    #[serde(default)]
+    pub mode: TestMode,
+    pub average_delay: f32,
+    pub median_delay: f32,
+    pub accuracy: f32,
+    pub rms_distance: f32,
+    pub peak_angular_error: f32,
+    #[serde(default)]
+    pub count_directions: usize,
+    #[serde(default)]
+    pub react_directions: usize,
+    // Flick: среднее время до попадания; режимы реакции: среднее время реакции, мс
+    #[serde(default)]
+    pub trial_time: f32,
+}
+
+impl HistoryEntry {
+    pub fn from_test(test: &ReactionTest, timestamp: u64) -> Self {
+        let mode = test.scenario.mode;
+        let (trial_time, accuracy) = match mode {
+            TestMode::Tracking => (0.0, test.accuracy()),
+            TestMode::Flick => {
+                let flick = &test.flick_summary;
+                (
+                    flick.average_time_to_hit,
+                    stats::percent(flick.hits, flick.trials),
+                )
+            }
+            TestMode::Reaction | TestMode::Choice | TestMode::GoNoGo => {
+                let reaction = &test.reaction_summary;
+                (
+                    reaction.mean,
+                    stats::percent(reaction.responses, reaction.trials),
+                )
+            }
+        };
+        let tracking = mode == TestMode::Tracking;
+        Self {
+            timestamp,
+            scenario: test.scenario.name.clone(),
+            seed: test.seed,
+            mode,
+            average_delay: if tracking { test.average_delay } else { 0.0 },
+            median_delay: if tracking { test.median_delay } else { 0.0 },
+            accuracy,
+            rms_distance: if tracking { test.rms_distance } else { 0.0 },
+            peak_angular_error: if tracking {
+                test.peak_angular_error
+            } else {
+                0.0
+            },
+            count_directions: if tracking { test.count_directions } else { 0 },
+            react_directions: if tracking { test.react_directions } else { 0 },
+            trial_time,
+        }
+    }
+
+    // Основное время теста, мс; None - в тесте не было ни одной реакции или попадания
+    pub fn time(&self) -> Option<f32> {
+        match self.mode {
+            TestMode::Tracking => (self.react_directions > 0).then_some(self.average_delay),
+            _ => (self.trial_time > 0.0).then_some(self.trial_time),
+        }
+    }
+}
+
+// Подписи основного времени и доли удачных попыток по режиму
+fn history_labels(mode: TestMode) -> (&'static str, &'static str) {
+    match mode {
+        TestMode::Tracking => ("Avg reaction", "Accuracy"),
+        TestMode::Flick => ("Time to hit", "Hit rate"),
+        TestMode::Reaction | TestMode::Choice | TestMode::GoNoGo => ("Reaction time", "Correct"),
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct RunHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub visible: bool,
+}
+
+impl RunHistory {
+    pub fn for_scenario<'a>(&'a self, scenario: &'a str) -> impl Iterator<Item = &'a HistoryEntry> {
+        self.entries.iter().filter(move |e| e.scenario == scenario)
+    }
+}
+
+// Система для загрузки истории при запуске
+pub fn load_history_system(mut history: ResMut<RunHistory>) {
+    history.entries = load_history(HISTORY_PATH);
+    info!(
+        "Loaded {} history entries from {}",
+        history.entries.len(),
+        HISTORY_PATH
+    );
+}
+
+// Система для записи каждого завершенного теста в историю
+pub fn record_run_history(
+    mut finished_events: EventReader<TestFinishedEvent>,
+    test: Res<ReactionTest>,
+    mut history: ResMut<RunHistory>,
+) {
+    for _event in finished_events.read() {
+        let entry = HistoryEntry::from_test(&test, unix_timestamp());
+        if let Err(e) = append_history_entry(&entry, HISTORY_PATH) {
+            error!("Failed to append run to history: {}", e);
+        }
+        history.entries.push(entry);
+    }
+}
+
+pub fn load_history(path: &str) -> Vec<HistoryEntry> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(i, line)| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                // Битую строку пропускаем, остальная история остается доступной
+                warn!("Skipping history line {} in {}: {}", i + 1, path, e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Файл только дописывается, одна JSON-запись на строку
+pub fn append_history_entry(
+    entry: &HistoryEntry,
+    path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+// Показ/скрытие истории в игре (только когда тест не идет)
+pub fn toggle_history_view(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    test: Res<ReactionTest>,
+    mut history: ResMut<RunHistory>,
+) {
+    if test.is_running {
+        if history.visible {
+            history.visible = false;
+        }
+        return;
+    }
+    if keys.just_pressed(settings.key_history) {
+        history.visible = !history.visible;
+    }
+}
+
+pub fn update_history_ui(
+    history: Res<RunHistory>,
+    settings: Res<Settings>,
+    mut panel_query: Query<&mut Visibility, With<HistoryPanel>>,
+    mut text_query: Query<&mut Text, With<HistoryText>>,
+) {
+    for mut visibility in panel_query.iter_mut() {
+        *visibility = if history.visible {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if !history.visible {
+        return;
+    }
+
+    if let Ok(mut text) = text_query.single_mut() {
+        text.0 = format_history(&history, &settings.scenario);
+    }
+}
+
+pub fn format_history(history: &RunHistory, scenario: &str) -> String {
+    let runs: Vec<&HistoryEntry> = history.for_scenario(scenario).collect();
+    let mut out = format!(
+        "HISTORY - {}\n{} runs ({} total)\n",
+        scenario,
+        runs.len(),
+        history.entries.len()
+    );
+
+    if runs.is_empty() {
+        out.push_str("\nNo completed runs yet");
+        return out;
+    }
+
+    // Формат по режиму последнего теста; записи другого режима (сценарий сменил режим,
+    // старые записи без поля mode) несравнимы с ним
+    let mode = runs[runs.len() - 1].mode;
+    let runs: Vec<&HistoryEntry> = runs.into_iter().filter(|e| e.mode == mode).collect();
+    let (time_label, accuracy_label) = history_labels(mode);
+    let tracking = mode == TestMode::Tracking;
+
+    // Тесты без найденных реакций или попаданий не участвуют в метриках времени
+    out.push_str("\nPERSONAL BESTS\n");
+    if let Some((best, time)) = runs
+        .iter()
+        .filter_map(|e| e.time().map(|time| (e, time)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+    {
+        let _ = writeln!(
+            out,
+            "{}: {:.1} ms ({})",
+            time_label,
+            time,
+            format_date(best.timestamp)
+        );
+    }
+    if let Some(best) = runs.iter().max_by(|a, b| a.accuracy.total_cmp(&b.accuracy)) {
+        let _ = writeln!(
+            out,
+            "{}: {:.2}% ({})",
+            accuracy_label,
+            best.accuracy,
+            format_date(best.timestamp)
+        );
+    }
+    if tracking {
+        if let Some(best) = runs
+            .iter()
+            .min_by(|a, b| a.rms_distance.total_cmp(&b.rms_distance))
+        {
+            let _ = writeln!(
+                out,
+                "Avg error: {:.3}° ({})",
+                best.rms_distance,
+                format_date(best.timestamp)
+            );
+        }
+    }
+
+    let window = HISTORY_ROLLING_WINDOW.min(runs.len());
+    let recent = &runs[runs.len() - window..];
+    let recent_times: Vec<f32> = recent.iter().filter_map(|e| e.time()).collect();
+    let _ = writeln!(out, "\nROLLING AVERAGE (last {})", window);
+    if let Some(time) = stats::mean(&recent_times) {
+        let _ = writeln!(out, "{}: {:.1} ms", time_label, time);
+    }
+    let _ = writeln!(
+        out,
+        "{}: {:.2}%",
+        accuracy_label,
+        recent.iter().map(|e| e.accuracy).sum::<f32>() / window as f32
+    );
+    if tracking {
+        let _ = writeln!(
+            out,
+            "Avg error: {:.3}°",
+            recent.iter().map(|e| e.rms_distance).sum::<f32>() / window as f32
+        );
+    }
+
+    out.push_str("\nLAST RUNS\n");
+    for entry in runs.iter().rev().take(5) {
+        let time = entry
+            .time()
+            .map_or("-".to_string(), |time| format!("{:.1} ms", time));
+        let _ = write!(
+            out,
+            "{}  {}  {:.1}%",
+            format_date(entry.timestamp),
+            time,
+            entry.accuracy
+        );
+        if tracking {
+            let _ = write!(out, "  {:.3}°", entry.rms_distance);
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+fn format_date(timestamp: u64) -> String {
+    // "YYYY-MM-DD_HH-MM-SS" -> "YYYY-MM-DD"
+    format_timestamp(timestamp)[..10].to_string()
+}
@@ -0,0 +1,3 @@
+pub mod export;
+pub mod history;
+pub mod replay_file;
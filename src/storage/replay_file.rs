@@ -0,0 +1,387 @@
+// Формат записи .mvrt:
+//   "MVRT" | версия формата u16 | длина заголовка u32 | заголовок JSON | тики, сжатые zlib
+// Все числа little-endian. Заголовок - RunExport без тиков и снимок GameConfig; новые поля
+// добавляются с serde(default), так что старые заголовки читаются и дальше. Раскладку тиков
+// меняем только вместе с версией формата: у каждой версии свой декодер в read_replay.
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Decompress, FlushDecompress, Status};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::export::{RunExport, RUN_EXPORT_VERSION};
+use crate::state::*;
+use bevy::prelude::Vec3;
+
+pub const REPLAY_MAGIC: &[u8; 4] = b"MVRT";
+pub const REPLAY_FORMAT_VERSION: u16 = 1;
+pub const REPLAY_EXTENSION: &str = "mvrt";
+
+// Каналы DataPoint в порядке записи (версия 1)
+const REPLAY_CHANNELS: usize = 14;
+// Запись может прийти от другого игрока: размеры из файла проверяем до выделения памяти
+const REPLAY_MAX_HEADER: usize = 64 << 20;
+// Самый длинный varint для u32
+const VARINT_MAX_BYTES: usize = 5;
+
+#[derive(Serialize, Deserialize)]
+struct ReplayHeader {
+    // Настройки игры на момент теста; Value, чтобы смена GameConfig не ломала старые записи
+    #[serde(default)]
+    config: serde_json::Value,
+    run: RunExport,
+    ticks: usize,
+}
+
+// Прочитанная запись: тест целиком и настройки, если их удалось разобрать
+pub struct Replay {
+    pub config: Option<GameConfig>,
+    pub run: RunExport,
+}
+
+pub fn save_replay(
+    path: &Path,
+    run: &RunExport,
+    config: &GameConfig,
+) -> Result<(), Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    write_replay(&mut bytes, run, config)?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+pub fn load_replay(path: &Path) -> Result<Replay, Box<dyn Error>> {
+    read_replay(&mut fs::File::open(path)?)
+}
+
+pub fn write_replay(
+    writer: &mut impl Write,
+    run: &RunExport,
+    config: &GameConfig,
+) -> Result<(), Box<dyn Error>> {
+    let mut header_run = run.clone();
+    header_run.data.clear();
+    let header = serde_json::to_vec(&ReplayHeader {
+        config: serde_json::to_value(config)?,
+        run: header_run,
+        ticks: run.data.len(),
+    })?;
+
+    writer.write_all(REPLAY_MAGIC)?;
+    writer.write_all(&REPLAY_FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&(header.len() as u32).to_le_bytes())?;
+    writer.write_all(&header)?;
+
+    let mut encoder = ZlibEncoder::new(writer, Compression::default());
+    encoder.write_all(&encode_ticks(&run.data))?;
+    encoder.finish()?;
+    Ok(())
+}
+
+pub fn read_replay(reader: &mut impl Read) -> Result<Replay, Box<dyn Error>> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != REPLAY_MAGIC {
+        return Err("not an .mvrt replay".into());
+    }
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+    match u16::from_le_bytes(version) {
+        1 => read_replay_v1(reader),
+        version => Err(format!(
+            "unsupported replay format version {} (max {})",
+            version, REPLAY_FORMAT_VERSION
+        )
+        .into()),
+    }
+}
+
+fn read_replay_v1(reader: &mut impl Read) -> Result<Replay, Box<dyn Error>> {
+    let mut length = [0u8; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_le_bytes(length) as usize;
+    if length > REPLAY_MAX_HEADER {
+        return Err(format!("replay header too large ({} bytes)", length).into());
+    }
+    // Через take буфер растет по мере чтения, а не по длине из файла
+    let mut header = Vec::new();
+    reader.take(length as u64).read_to_end(&mut header)?;
+    if header.len() < length {
+        return Err("truncated replay header".into());
+    }
+    let header: ReplayHeader = serde_json::from_slice(&header)?;
+    if header.run.version > RUN_EXPORT_VERSION {
+        return Err(format!(
+            "unsupported run export version {} (max {})",
+            header.run.version, RUN_EXPORT_VERSION
+        )
+        .into());
+    }
+
+    // Больше, чем по самому длинному varint на значение, тики занимать не могут
+    let limit = header
+        .ticks
+        .checked_mul(REPLAY_CHANNELS * VARINT_MAX_BYTES)
+        .ok_or("replay tick count too large")?;
+    let mut compressed = Vec::new();
+    reader.read_to_end(&mut compressed)?;
+    let body = inflate(&compressed, limit)?;
+
+    let mut run = header.run;
+    run.data = decode_ticks(&body, header.ticks)?;
+    Ok(Replay {
+        config: serde_json::from_value(header.config).ok(),
+        run,
+    })
+}
+
+// Поток zlib целиком, с контрольной суммой: ZlibDecoder молча принимает обрезанный хвост.
+// Распаковывает не больше limit байт: маленький файл не должен раздуться на всю память
+fn inflate(compressed: &[u8], limit: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut decompress = Decompress::new(true);
+    let mut body = Vec::new();
+    loop {
+        // decompress_vec пишет только в свободную емкость; байт сверх limit - признак превышения
+        let room = (limit + 1 - body.len()).min(compressed.len().max(1024));
+        body.reserve_exact(room);
+        let consumed = decompress.total_in() as usize;
+        let produced = body.len();
+        let status =
+            decompress.decompress_vec(&compressed[consumed..], &mut body, FlushDecompress::None)?;
+        if body.len() > limit {
+            return Err("replay data larger than its tick count".into());
+        }
+        if status == Status::StreamEnd {
+            return Ok(body);
+        }
+        // Вход кончился, а конца потока нет
+        if decompress.total_in() as usize == consumed && body.len() == produced {
+            return Err("truncated replay data".into());
+        }
+    }
+}
+
+fn channels(point: &DataPoint) -> [f32; REPLAY_CHANNELS] {
+    [
+        point.time,
+        point.target_pos.x,
+        point.target_pos.y,
+        point.target_pos.z,
+        point.crosshair_dir.x,
+        point.crosshair_dir.y,
+        point.crosshair_dir.z,
+        point.camera_pos.x,
+        point.camera_pos.y,
+        point.camera_pos.z,
+        point.target_x,
+        point.target_y,
+        point.crosshair_x,
+        point.crosshair_y,
+    ]
+}
+
+// Без потерь: по каналам подряд разности битовых представлений f32 соседних тиков
+// (zigzag + varint). Соседние тики близки, поэтому разности короткие и хорошо жмутся
+fn encode_ticks(data: &[DataPoint]) -> Vec<u8> {
+    let rows: Vec<[f32; REPLAY_CHANNELS]> = data.iter().map(channels).collect();
+    let mut bytes = Vec::new();
+    for channel in 0..REPLAY_CHANNELS {
+        let mut previous = 0u32;
+        for row in &rows {
+            let bits = row[channel].to_bits();
+            let delta = bits.wrapping_sub(previous) as i32;
+            write_varint(&mut bytes, ((delta << 1) ^ (delta >> 31)) as u32);
+            previous = bits;
+        }
+    }
+    bytes
+}
+
+fn decode_ticks(bytes: &[u8], ticks: usize) -> Result<Vec<DataPoint>, Box<dyn Error>> {
+    // На каждое значение хотя бы один байт varint
+    if ticks
+        .checked_mul(REPLAY_CHANNELS)
+        .is_none_or(|values| values > bytes.len())
+    {
+        return Err("truncated replay data".into());
+    }
+    let mut rows = vec![[0f32; REPLAY_CHANNELS]; ticks];
+    let mut position = 0;
+    for channel in 0..REPLAY_CHANNELS {
+        let mut previous = 0u32;
+        for row in rows.iter_mut() {
+            let zigzag = read_varint(bytes, &mut position).ok_or("truncated replay data")?;
+            let delta = ((zigzag >> 1) as i32) ^ -((zigzag & 1) as i32);
+            previous = previous.wrapping_add(delta as u32);
+            row[channel] = f32::from_bits(previous);
+        }
+    }
+
+    Ok(rows
+        .iter()
+        .map(|c| DataPoint {
+            time: c[0],
+            target_pos: Vec3::new(c[1], c[2], c[3]),
+            crosshair_dir: Vec3::new(c[4], c[5], c[6]),
+            camera_pos: Vec3::new(c[7], c[8], c[9]),
+            target_x: c[10],
+            target_y: c[11],
+            crosshair_x: c[12],
+            crosshair_y: c[13],
+        })
+        .collect())
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], position: &mut usize) -> Option<u32> {
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes.get(*position)?;
+        *position += 1;
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Значения, на которых ломаются дельты битов: знак, нуль со знаком, NaN, бесконечности
+    const SPECIAL: [f32; 8] = [
+        0.0,
+        -0.0,
+        f32::NAN,
+        -1.5,
+        f32::MIN_POSITIVE,
+        f32::MAX,
+        f32::NEG_INFINITY,
+        -1e-30,
+    ];
+
+    fn run_with(data: Vec<DataPoint>) -> RunExport {
+        let mut run = RunExport::from_test(&ReactionTest::default(), 0);
+        run.data = data;
+        run
+    }
+
+    fn special_data(ticks: usize) -> Vec<DataPoint> {
+        (0..ticks)
+            .map(|i| {
+                let v = |channel: usize| SPECIAL[(i + channel) % SPECIAL.len()];
+                DataPoint {
+                    time: i as f32 * 0.001,
+                    target_pos: Vec3::new(v(1), v(2), v(3)),
+                    crosshair_dir: Vec3::new(v(4), v(5), v(6)),
+                    camera_pos: Vec3::new(v(7), v(8), v(9)),
+                    target_x: v(10),
+                    target_y: v(11),
+                    crosshair_x: v(12),
+                    crosshair_y: v(13),
+                }
+            })
+            .collect()
+    }
+
+    fn encode(run: &RunExport) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_replay(&mut bytes, run, &GameConfig::default()).unwrap();
+        bytes
+    }
+
+    fn bits(data: &[DataPoint]) -> Vec<[u32; REPLAY_CHANNELS]> {
+        data.iter().map(|p| channels(p).map(f32::to_bits)).collect()
+    }
+
+    #[test]
+    fn round_trip_keeps_every_bit() {
+        let run = run_with(special_data(100));
+        let replay = read_replay(&mut encode(&run).as_slice()).unwrap();
+        assert_eq!(bits(&replay.run.data), bits(&run.data));
+        assert!(replay.config.is_some());
+    }
+
+    #[test]
+    fn round_trip_empty_run() {
+        let replay = read_replay(&mut encode(&run_with(Vec::new())).as_slice()).unwrap();
+        assert!(replay.run.data.is_empty());
+    }
+
+    #[test]
+    fn truncated_file_is_error() {
+        let bytes = encode(&run_with(special_data(100)));
+        for length in [0, 5, 12, bytes.len() / 2, bytes.len() - 10, bytes.len() - 1] {
+            assert!(
+                read_replay(&mut &bytes[..length]).is_err(),
+                "{} of {} bytes",
+                length,
+                bytes.len()
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_body_is_error() {
+        let body = encode_ticks(&special_data(100));
+        assert!(decode_ticks(&body[..body.len() - 1], 100).is_err());
+        assert!(decode_ticks(&body, 101).is_err());
+    }
+
+    #[test]
+    fn unknown_version_is_error() {
+        let mut bytes = encode(&run_with(special_data(10)));
+        bytes[4..6].copy_from_slice(&(REPLAY_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(read_replay(&mut bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn bad_magic_is_error() {
+        let mut bytes = encode(&run_with(special_data(10)));
+        bytes[0] = b'X';
+        assert!(read_replay(&mut bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn oversized_header_is_error() {
+        let mut bytes = encode(&run_with(special_data(10)));
+        bytes[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read_replay(&mut bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn huge_tick_count_is_error() {
+        assert!(decode_ticks(&[0; 32], usize::MAX).is_err());
+        assert!(decode_ticks(&[0; 32], 1 << 40).is_err());
+    }
+
+    #[test]
+    fn oversized_body_is_error() {
+        // Мегабайт нулей сжимается примерно в килобайт, а на один тик приходится не больше 70 байт
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&vec![0; 1 << 20]).unwrap();
+        let bomb = encoder.finish().unwrap();
+        let limit = REPLAY_CHANNELS * VARINT_MAX_BYTES;
+        assert!(bomb.len() < limit * 100);
+        assert!(inflate(&bomb, limit).is_err());
+
+        let body = encode_ticks(&special_data(100));
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&body).unwrap();
+        assert_eq!(
+            inflate(&encoder.finish().unwrap(), body.len()).unwrap(),
+            body
+        );
+    }
+}
@@ -0,0 +1,180 @@
+// Графики экрана результатов рисуются в текстуры: тысячи точек теста нодами bevy_ui не нарисовать
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::state::*;
+use crate::target::calculate_angular_error;
+use crate::user_interface::ui::UI_COLORS;
+
+// Размеры текстур в пикселях
+pub const ERROR_CHART_SIZE: UVec2 = UVec2::new(720, 180);
+pub const TRAJECTORY_SIZE: UVec2 = UVec2::new(240, 240);
+// Линии сетки графика ошибки
+const ERROR_CHART_GRID: u32 = 4;
+
+// RGBA-буфер с рисованием линий; y растет вверх, как на графике
+struct Canvas {
+    size: UVec2,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(size: UVec2, background: Color) -> Self {
+        let pixel = background.to_srgba().to_u8_array();
+        Self {
+            size,
+            pixels: pixel.repeat((size.x * size.y) as usize),
+        }
+    }
+
+    fn dot(&mut self, x: i32, y: i32, color: [u8; 4]) {
+        // Толщина линии - 2 пикселя
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let (px, py) = (x + dx, y + dy);
+            if px < 0 || py < 0 || px >= self.size.x as i32 || py >= self.size.y as i32 {
+                continue;
+            }
+            let row = self.size.y as i32 - 1 - py;
+            let index = ((row * self.size.x as i32 + px) * 4) as usize;
+            self.pixels[index..index + 4].copy_from_slice(&color);
+        }
+    }
+
+    fn line(&mut self, from: Vec2, to: Vec2, color: Color) {
+        let color = color.to_srgba().to_u8_array();
+        let steps = (to - from).abs().max_element().ceil().max(1.0) as i32;
+        for step in 0..=steps {
+            let point = from.lerp(to, step as f32 / steps as f32);
+            self.dot(point.x.round() as i32, point.y.round() as i32, color);
+        }
+    }
+
+    fn polyline(&mut self, points: &[Vec2], color: Color) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color);
+        }
+    }
+
+    fn into_image(self) -> Image {
+        Image::new(
+            Extent3d {
+                width: self.size.x,
+                height: self.size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.pixels,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        )
+    }
+}
+
+// Угловая ошибка по времени: по среднему на каждый столбец пикселей.
+// Возвращает текстуру и верх шкалы в градусах
+pub fn angular_error_chart(data: &[DataPoint], warmup: f32) -> (Image, f32) {
+    let size = ERROR_CHART_SIZE;
+    let mut canvas = Canvas::new(size, UI_COLORS.surface);
+    let duration = data.last().map_or(0.0, |p| p.time).max(f32::EPSILON);
+
+    let mut columns = vec![(0.0f32, 0u32); size.x as usize];
+    for point in data {
+        let error =
+            calculate_angular_error(point.target_pos - point.camera_pos, point.crosshair_dir);
+        let column = ((point.time / duration * size.x as f32) as usize).min(size.x as usize - 1);
+        columns[column].0 += error;
+        columns[column].1 += 1;
+    }
+    let means: Vec<(usize, f32)> = columns
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, count))| *count > 0)
+        .map(|(x, (sum, count))| (x, sum / *count as f32))
+        .collect();
+    let max_error = means
+        .iter()
+        .map(|(_, error)| *error)
+        .fold(0.0, f32::max)
+        .max(0.1)
+        * 1.1;
+
+    let height = (size.y - 1) as f32;
+    for line in 1..ERROR_CHART_GRID {
+        let y = height * line as f32 / ERROR_CHART_GRID as f32;
+        canvas.line(
+            Vec2::new(0.0, y),
+            Vec2::new(size.x as f32, y),
+            UI_COLORS.surface_light,
+        );
+    }
+    // Конец разминки
+    if warmup > 0.0 {
+        let x = warmup / duration * size.x as f32;
+        canvas.line(
+            Vec2::new(x, 0.0),
+            Vec2::new(x, height),
+            UI_COLORS.text_muted,
+        );
+    }
+    let points: Vec<Vec2> = means
+        .iter()
+        .map(|(x, error)| Vec2::new(*x as f32, error / max_error * height))
+        .collect();
+    canvas.polyline(&points, UI_COLORS.danger);
+
+    (canvas.into_image(), max_error)
+}
+
+// Пути таргета и прицела в плоскости экрана в общем масштабе, чтобы их можно было сравнить.
+// target_x/y - в единицах мира, crosshair_x/y - от единичного направления: прицел
+// переносим на дистанцию таргета. На скачках трассы линия прерывается
+pub fn trajectory_charts(data: &[DataPoint], jumps: &[f32]) -> (Image, Image) {
+    let target: Vec<Vec2> = data
+        .iter()
+        .map(|p| Vec2::new(p.target_x, p.target_y))
+        .collect();
+    let crosshair: Vec<Vec2> = data
+        .iter()
+        .map(|p| Vec2::new(p.crosshair_x, p.crosshair_y) * (p.target_pos - p.camera_pos).length())
+        .collect();
+    let extent = target
+        .iter()
+        .chain(crosshair.iter())
+        .map(|p| p.abs().max_element())
+        .fold(0.0, f32::max)
+        .max(f32::EPSILON)
+        * 1.05;
+    let plot = |points: &[Vec2], color: Color| {
+        let size = TRAJECTORY_SIZE.as_vec2();
+        let mut canvas = Canvas::new(TRAJECTORY_SIZE, UI_COLORS.surface);
+        let center = (size - Vec2::ONE) / 2.0;
+        canvas.line(
+            Vec2::new(0.0, center.y),
+            Vec2::new(size.x, center.y),
+            UI_COLORS.surface_light,
+        );
+        canvas.line(
+            Vec2::new(center.x, 0.0),
+            Vec2::new(center.x, size.y),
+            UI_COLORS.surface_light,
+        );
+        let scaled: Vec<Vec2> = points
+            .iter()
+            .map(|p| center + *p / extent * center)
+            .collect();
+        let mut start = 0;
+        for &jump in jumps {
+            let end = data.partition_point(|p| p.time < jump).max(start);
+            canvas.polyline(&scaled[start..end], color);
+            start = end;
+        }
+        canvas.polyline(&scaled[start..], color);
+        canvas.into_image()
+    };
+
+    (
+        plot(&target, UI_COLORS.warning),
+        plot(&crosshair, UI_COLORS.danger),
+    )
+}
@@ -1,3 +1,5 @@
+pub mod charts;
+pub mod results;
 pub mod ui;
 pub mod ui_components;
 
@@ -0,0 +1,701 @@
+// Экран результатов: сводка, значок рейтинга и графики по записи теста
+use crate::game::{launch_test, reset_test};
+use crate::ghost::check_ghost_scenario;
+use crate::staircase::staircase_parameter_name;
+use crate::state::*;
+use crate::target::trace_jumps;
+use crate::user_interface::charts::*;
+use crate::user_interface::ui::UI_COLORS;
+use crate::xcorr;
+use bevy::prelude::*;
+
+// Столбцов в гистограмме задержек
+const HISTOGRAM_BINS: usize = 12;
+const HISTOGRAM_HEIGHT: f32 = 160.0;
+
+// Рейтинг по средней задержке слежения и цвет значка
+pub fn rating(average_delay: f32) -> (&'static str, Color) {
+    match average_delay {
+        d if d < 125.0 => ("Supreme", Color::hsl(0.0, 0.8, 0.55)),
+        d if d < 135.0 => ("Grandmaster", Color::hsl(330.0, 0.7, 0.55)),
+        d if d < 150.0 => ("Master", Color::hsl(280.0, 0.7, 0.55)),
+        d if d < 165.0 => ("Diamond", Color::hsl(200.0, 0.8, 0.55)),
+        d if d < 180.0 => ("Platinum", Color::hsl(170.0, 0.5, 0.5)),
+        d if d < 200.0 => ("Gold", Color::hsl(45.0, 0.9, 0.5)),
+        d if d < 220.0 => ("Silver", Color::hsl(220.0, 0.1, 0.65)),
+        d if d < 250.0 => ("Bronze", Color::hsl(25.0, 0.6, 0.45)),
+        _ => ("Keep practicing", UI_COLORS.surface_light),
+    }
+}
+
+fn results_title(test: &ReactionTest) -> &'static str {
+    match test.scenario.mode {
+        TestMode::Tracking => "TEST RESULTS",
+        TestMode::Flick => "FLICK RESULTS",
+        TestMode::Reaction => "REACTION RESULTS",
+        TestMode::Choice => "CHOICE REACTION RESULTS",
+        TestMode::GoNoGo => "GO/NO-GO RESULTS",
+    }
+}
+
+// Задержки для гистограммы, мс: реакции на смены направления, время попадания или реакции
+fn reaction_delays(test: &ReactionTest) -> (&'static str, Vec<f32>) {
+    match test.scenario.mode {
+        TestMode::Tracking => (
+            "Reaction delays",
+            test.reactions.iter().map(|r| r.delay * 1000.0).collect(),
+        ),
+        TestMode::Flick => (
+            "Time to hit",
+            test.flick_trials
+                .iter()
+                .filter_map(|t| t.time_to_hit)
+                .map(|t| t * 1000.0)
+                .collect(),
+        ),
+        _ => (
+            "Reaction times",
+            test.reaction_trials
+                .iter()
+                .filter_map(|t| t.reaction_time)
+                .map(|t| t * 1000.0)
+                .collect(),
+        ),
+    }
+}
+
+// Равные столбцы от минимума до максимума
+fn histogram(values: &[f32]) -> (Vec<u32>, f32, f32) {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let width = ((max - min) / HISTOGRAM_BINS as f32).max(f32::EPSILON);
+    let mut bins = vec![0u32; HISTOGRAM_BINS];
+    for value in values {
+        let bin = (((value - min) / width) as usize).min(HISTOGRAM_BINS - 1);
+        bins[bin] += 1;
+    }
+    (bins, min, max)
+}
+
+pub fn format_tracking_results(
+    test: &ReactionTest,
+    settings: &Settings,
+    ghost: &GhostRace,
+) -> String {
+    let xcorr_segments = match xcorr::xcorr_segment_median(test) {
+        Some(median) => format!("{:.1} ms", median),
+        None => "-".to_string(),
+    };
+
+    // Все детекторы; активный помечен "*"
+    let detector_lines: String = test
+        .detector_reports
+        .iter()
+        .map(|report| {
+            format!(
+                "\n {} {}: {:.1} ms (median {:.1} ms, {}/{})",
+                if report.name == settings.detector {
+                    "*"
+                } else {
+                    " "
+                },
+                report.name,
+                report.average_delay,
+                report.median_delay,
+                report.react_directions,
+                report.count_directions
+            )
+        })
+        .collect();
+
+    // Переключения между таргетами
+    let switch_lines = if test.switch_summary.switches > 0 {
+        format!(
+            "\nSwitches: {}/{}\nSwitch time: {:.1} ms (median {:.1} ms)",
+            test.switch_summary.acquired,
+            test.switch_summary.switches,
+            test.switch_summary.average_switch_time,
+            test.switch_summary.median_switch_time
+        )
+    } else {
+        String::new()
+    };
+
+    // Порог адаптивной лестницы
+    let staircase_lines = match &test.scenario.staircase {
+        Some(staircase) => {
+            let summary = &test.staircase_summary;
+            let unit = staircase_parameter_name(staircase.parameter).1;
+            format!(
+                "\nThreshold {}: {:.2} {} ({} reversals, {} blocks)\n  start {:.2}, final {:.2}",
+                summary.parameter,
+                summary.threshold,
+                unit,
+                summary.reversals,
+                summary.blocks,
+                summary.start_value,
+                summary.final_value
+            )
+        }
+        None => String::new(),
+    };
+
+    // Убийства в режиме со здоровьем
+    let kill_lines = if test.scenario.target_health > 0.0 {
+        let summary = &test.kill_summary;
+        format!(
+            "\nKills: {}\nTime to kill: {:.1} ms (median {:.1} ms)\nDamage efficiency: {:.1}%",
+            summary.kills,
+            summary.average_time_to_kill,
+            summary.median_time_to_kill,
+            summary.damage_efficiency * 100.0
+        )
+    } else {
+        String::new()
+    };
+
+    // Точность по угловому размеру таргета (если дистанция менялась)
+    let size_lines: String = test
+        .size_bins
+        .iter()
+        .map(|bin| {
+            format!(
+                "\n  {:.2}-{:.2}°: {:.3}°, on target {:.0}% ({:.0}% of time)",
+                bin.min_size,
+                bin.max_size,
+                bin.average_error,
+                bin.on_target * 100.0,
+                bin.time_share * 100.0
+            )
+        })
+        .collect();
+    let size_lines = if size_lines.is_empty() {
+        size_lines
+    } else {
+        format!("\nBy target size:{}", size_lines)
+    };
+
+    // Итог гонки с призраком
+    let ghost_line = if ghost.racing {
+        format!(
+            "\nVs ghost ({}): {:.2} s vs {:.2} s on target ({:+.2} s)",
+            ghost.title,
+            ghost.live_on_target,
+            ghost.ghost_on_target,
+            ghost.lead()
+        )
+    } else {
+        String::new()
+    };
+
+    let warmup_line = if test.scenario.warmup > 0.0 {
+        format!("\nWarm-up excluded: {:.1} s", test.scenario.warmup)
+    } else {
+        String::new()
+    };
+
+    // Разброс задержек и доля найденных реакций
+    let delays = &test.delay_stats;
+    let detection_warning = if delays.low_detection_rate() {
+        format!(
+            "\n  Low detection rate (< {:.0}%): delay estimate is unreliable",
+            MIN_DETECTION_RATE * 100.0
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        "Avg Reaction: {:.1} ms (95% CI {:.1}-{:.1})
+  Median {:.1} ms, SD {:.1} ms
+  Range {:.0}-{:.0} ms, P10/P90 {:.0}/{:.0} ms
+Count Dirs: {}
+React Dirs: {} ({:.0}% detected){}
+Estimators:{}
+  X-Corr: {:.1} ms (r = {:.2})
+  X-Corr segments: {} ({}/{})
+Accuracy {:.2}%
+Hits: {}
+Miss: {}
+Avg error: {:.4}°
+Peak error: {:.4}°{}{}{}{}{}{}",
+        test.average_delay,
+        delays.ci_low,
+        delays.ci_high,
+        test.median_delay,
+        delays.std_dev,
+        delays.min,
+        delays.max,
+        delays.p10,
+        delays.p90,
+        test.count_directions,
+        test.react_directions,
+        delays.detection_rate * 100.0,
+        detection_warning,
+        detector_lines,
+        test.xcorr_delay,
+        test.xcorr_correlation,
+        xcorr_segments,
+        test.xcorr_segment_delays.len(),
+        test.xcorr_segment_count,
+        test.accuracy(),
+        test.hits,
+        test.misses,
+        test.rms_distance,
+        test.peak_angular_error,
+        warmup_line,
+        size_lines,
+        switch_lines,
+        kill_lines,
+        staircase_lines,
+        ghost_line
+    )
+}
+
+pub fn format_flick_results(test: &ReactionTest) -> String {
+    let summary = &test.flick_summary;
+    format!(
+        "Trials: {}
+Hits: {}/{}
+Misses: {}
+Time to first move: {:.1} ms
+Time to hit: {:.1} ms
+Overshoot: {:.2}°
+Fitts: r = {:.2}
+  time = {:.0} + {:.0} * ID ms",
+        summary.trials,
+        summary.hits,
+        summary.trials,
+        test.misses,
+        summary.average_first_move,
+        summary.average_time_to_hit,
+        summary.average_overshoot,
+        summary.fitts_correlation,
+        summary.fitts_intercept,
+        summary.fitts_slope
+    )
+}
+
+pub fn format_reaction_results(test: &ReactionTest) -> String {
+    let summary = &test.reaction_summary;
+
+    // Слуховые попытки: сравнение со зрительными и поправка на задержку звука
+    let audio_lines = if test.reaction_trials.iter().any(|t| t.auditory) {
+        let visual = if test.reaction_trials.iter().any(|t| !t.auditory) {
+            format!("{:.1} ms", summary.visual_mean)
+        } else {
+            "-".to_string()
+        };
+        format!(
+            "\nVisual: {}\nAuditory: {:.1} ms\nSound start delay: {:.1} ms (measured)\nAudio output latency: {:.1} ms (config)\nAuditory corrected: {:.1} ms",
+            visual,
+            summary.auditory_mean,
+            summary.sound_start_delay,
+            test.run_settings.audio_output_latency,
+            summary.auditory_corrected_mean
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        "Trials: {}
+Correct responses: {}
+False starts: {}
+Commission errors: {}
+Omission errors: {}
+Mean: {:.1} ms
+Median: {:.1} ms
+SD: {:.1} ms{}",
+        summary.trials,
+        summary.responses,
+        summary.false_starts,
+        summary.commission_errors,
+        summary.omission_errors,
+        summary.mean,
+        summary.median,
+        summary.sd,
+        audio_lines
+    )
+}
+
+// Группа на строку; самая медленная отмечена, чтобы асимметрия сразу бросалась в глаза
+fn format_direction_bins(title: &str, bins: &[DirectionBin]) -> String {
+    let slowest = bins
+        .iter()
+        .filter(|b| b.reactions > 0)
+        .max_by(|a, b| a.average_delay.total_cmp(&b.average_delay))
+        .map(|b| b.label.as_str());
+    let mut lines = title.to_string();
+    for bin in bins {
+        lines.push_str(&format!(
+            "\n{}: {:.1} ms ({}/{}), {:.2}°{}",
+            bin.label,
+            bin.average_delay,
+            bin.reactions,
+            bin.changes,
+            bin.average_error,
+            if bins.len() > 1 && slowest == Some(bin.label.as_str()) {
+                " - slowest"
+            } else {
+                ""
+            }
+        ));
+    }
+    lines
+}
+
+// Тест закончился - переходим на экран результатов
+pub fn show_results_on_finish(
+    mut finished_events: EventReader<TestFinishedEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if finished_events.read().count() > 0 {
+        next_state.set(AppState::Results);
+    }
+}
+
+fn results_hint(test: &ReactionTest, ghost: &GhostRace) -> String {
+    let mut hint = String::from("SPACE - new test\nR - restart\nP - replay");
+    if ghost.is_active() {
+        hint.push_str("\nG - clear ghost");
+    } else if check_ghost_scenario(&test.scenario).is_ok() {
+        hint.push_str("\nG - race this run");
+    }
+    hint.push_str("\nH - history\nESC - settings");
+    hint
+}
+
+pub fn setup_results_ui(
+    mut commands: Commands,
+    test: Res<ReactionTest>,
+    settings: Res<Settings>,
+    ghost: Res<GhostRace>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let font: Handle<Font> = asset_server.load(&settings.font_file);
+    let text = |value: String, size: f32, color: Color| {
+        (
+            Text::new(value),
+            TextFont {
+                font: font.clone(),
+                font_size: size,
+                ..default()
+            },
+            TextColor(color),
+        )
+    };
+    let stats = match test.scenario.mode {
+        TestMode::Tracking => format_tracking_results(&test, &settings, &ghost),
+        TestMode::Flick => format_flick_results(&test),
+        _ => format_reaction_results(&test),
+    };
+
+    let root = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(24.0)),
+                column_gap: Val::Px(24.0),
+                ..default()
+            },
+            BackgroundColor(UI_COLORS.background),
+            GlobalZIndex(1),
+            ResultsUI,
+        ))
+        .id();
+
+    // Слева сводка: заголовок, рейтинг, цифры и клавиши
+    let summary = commands
+        .spawn((
+            Node {
+                width: Val::Px(400.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                overflow: Overflow::clip_y(),
+                ..default()
+            },
+            ChildOf(root),
+        ))
+        .id();
+    commands.spawn((
+        text(
+            results_title(&test).to_string(),
+            28.0,
+            UI_COLORS.text_primary,
+        ),
+        ChildOf(summary),
+    ));
+    commands.spawn((
+        text(
+            format!("{}, seed {}", test.scenario.name, test.seed),
+            16.0,
+            UI_COLORS.text_secondary,
+        ),
+        ChildOf(summary),
+    ));
+    // Рейтинг считается только по задержке слежения
+    if test.scenario.mode == TestMode::Tracking {
+        let (name, color) = rating(test.average_delay);
+        commands
+            .spawn((
+                Node {
+                    padding: UiRect::axes(Val::Px(16.0), Val::Px(10.0)),
+                    align_self: AlignSelf::FlexStart,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                BackgroundColor(color),
+                BorderRadius::all(Val::Px(12.0)),
+                ChildOf(summary),
+            ))
+            .with_children(|badge| {
+                badge.spawn(text(name.to_string(), 26.0, UI_COLORS.text_primary));
+                badge.spawn(text(
+                    format!("{:.1} ms", test.average_delay),
+                    16.0,
+                    UI_COLORS.text_primary,
+                ));
+            });
+    }
+    commands.spawn((text(stats, 15.0, UI_COLORS.text_primary), ChildOf(summary)));
+    commands.spawn((
+        text(results_hint(&test, &ghost), 15.0, UI_COLORS.text_secondary),
+        ResultsHintText,
+        ChildOf(summary),
+    ));
+
+    // Справа графики
+    let charts = commands
+        .spawn((
+            Node {
+                flex_grow: 1.0,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            ChildOf(root),
+        ))
+        .id();
+    let card = |commands: &mut Commands, parent: Entity, title: String, flex_grow: f32| {
+        let card = commands
+            .spawn((
+                Node {
+                    flex_grow,
+                    padding: UiRect::all(Val::Px(12.0)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                BackgroundColor(UI_COLORS.surface_light),
+                BorderRadius::all(Val::Px(12.0)),
+                ChildOf(parent),
+            ))
+            .id();
+        commands.spawn((text(title, 16.0, UI_COLORS.text_secondary), ChildOf(card)));
+        card
+    };
+    let no_data = || text("No data".to_string(), 15.0, UI_COLORS.text_muted);
+
+    let duration = test.data.last().map_or(0.0, |p| p.time);
+    let (error_chart, max_error) = angular_error_chart(&test.data, test.scenario.warmup);
+    let error_card = card(
+        &mut commands,
+        charts,
+        format!(
+            "Angular error over time (0-{:.1}°, 0-{:.0} s)",
+            max_error, duration
+        ),
+        0.0,
+    );
+    if test.data.is_empty() {
+        commands.spawn((no_data(), ChildOf(error_card)));
+    } else {
+        commands.spawn((
+            ImageNode::new(images.add(error_chart)),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(ERROR_CHART_SIZE.y as f32),
+                ..default()
+            },
+            ChildOf(error_card),
+        ));
+    }
+
+    let bottom = commands
+        .spawn((
+            Node {
+                column_gap: Val::Px(16.0),
+                ..default()
+            },
+            ChildOf(charts),
+        ))
+        .id();
+
+    let (delay_title, delays) = reaction_delays(&test);
+    let histogram_card = card(
+        &mut commands,
+        bottom,
+        format!("{} (n = {})", delay_title, delays.len()),
+        1.0,
+    );
+    if delays.is_empty() {
+        commands.spawn((no_data(), ChildOf(histogram_card)));
+    } else {
+        let (bins, min, max) = histogram(&delays);
+        let peak = bins.iter().copied().max().unwrap_or(1).max(1);
+        commands
+            .spawn((
+                Node {
+                    height: Val::Px(HISTOGRAM_HEIGHT),
+                    align_items: AlignItems::FlexEnd,
+                    column_gap: Val::Px(2.0),
+                    ..default()
+                },
+                ChildOf(histogram_card),
+            ))
+            .with_children(|bars| {
+                for count in bins {
+                    bars.spawn((
+                        Node {
+                            flex_grow: 1.0,
+                            flex_basis: Val::Px(0.0),
+                            height: Val::Percent(count as f32 / peak as f32 * 100.0),
+                            ..default()
+                        },
+                        BackgroundColor(UI_COLORS.primary),
+                    ));
+                }
+            });
+        commands
+            .spawn((
+                Node {
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                ChildOf(histogram_card),
+            ))
+            .with_children(|axis| {
+                axis.spawn(text(format!("{:.0} ms", min), 14.0, UI_COLORS.text_muted));
+                axis.spawn(text(format!("{:.0} ms", max), 14.0, UI_COLORS.text_muted));
+            });
+    }
+
+    // Задержка (реакции/смены) и ошибка по направлению, оси разворота и резкости поворота
+    let breakdown = &test.direction_breakdown;
+    if test.scenario.mode == TestMode::Tracking && !breakdown.by_direction.is_empty() {
+        let directions_card = card(
+            &mut commands,
+            charts,
+            "Reactions by direction: delay (reacted/changes), error".to_string(),
+            0.0,
+        );
+        commands
+            .spawn((
+                Node {
+                    column_gap: Val::Px(32.0),
+                    ..default()
+                },
+                ChildOf(directions_card),
+            ))
+            .with_children(|row| {
+                for (title, bins) in [
+                    ("Direction", &breakdown.by_direction),
+                    ("Reversal axis", &breakdown.by_axis),
+                    ("Turn angle", &breakdown.by_turn),
+                ] {
+                    row.spawn(text(
+                        format_direction_bins(title, bins),
+                        15.0,
+                        UI_COLORS.text_primary,
+                    ));
+                }
+            });
+    }
+
+    let (target_path, crosshair_path) =
+        trajectory_charts(&test.data, &trace_jumps(&test.target_switches, &test.kills));
+    for (title, image) in [
+        ("Target path", target_path),
+        ("Crosshair path", crosshair_path),
+    ] {
+        let path_card = card(&mut commands, bottom, title.to_string(), 0.0);
+        if test.data.is_empty() {
+            commands.spawn((no_data(), ChildOf(path_card)));
+            continue;
+        }
+        commands.spawn((
+            ImageNode::new(images.add(image)),
+            Node {
+                width: Val::Px(TRAJECTORY_SIZE.x as f32),
+                height: Val::Px(TRAJECTORY_SIZE.y as f32),
+                ..default()
+            },
+            ChildOf(path_card),
+        ));
+    }
+}
+
+pub fn cleanup_results_ui(mut commands: Commands, query: Query<Entity, With<ResultsUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// Подсказка зависит от призрака: G его то включает, то убирает
+pub fn update_results_hint(
+    test: Res<ReactionTest>,
+    ghost: Res<GhostRace>,
+    mut hint_query: Query<&mut Text, With<ResultsHintText>>,
+) {
+    if !ghost.is_changed() {
+        return;
+    }
+    for mut text in hint_query.iter_mut() {
+        text.0 = results_hint(&test, &ghost);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn results_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut test: ResMut<ReactionTest>,
+    mut next_state: ResMut<NextState<AppState>>,
+    time_fixed: Res<Time<Fixed>>,
+    mut camera_query: Query<&mut Transform, With<PlayerCamera>>,
+    mut target_query: Query<&mut Transform, (With<Target>, Without<PlayerCamera>)>,
+    settings: Res<Settings>,
+    scenarios: Res<ScenarioLibrary>,
+    mut ghost: ResMut<GhostRace>,
+    mut viewer: ResMut<ReplayViewer>,
+) {
+    if keys.just_pressed(settings.key_start) || keys.just_pressed(settings.key_restart) {
+        if keys.just_pressed(settings.key_restart) {
+            reset_test(&mut test);
+        }
+        launch_test(
+            &mut test,
+            &time_fixed,
+            &settings,
+            &scenarios,
+            &mut ghost,
+            &mut camera_query,
+            &mut target_query,
+        );
+        next_state.set(AppState::Game);
+    } else if keys.just_pressed(settings.key_replay) && !test.data.is_empty() {
+        *viewer = ReplayViewer::open(
+            format!("{}, seed {}", test.scenario.name, test.seed),
+            test.data.clone(),
+            test.direction_changes.clone(),
+            test.reactions.clone(),
+            trace_jumps(&test.target_switches, &test.kills),
+        );
+        next_state.set(AppState::Game);
+    } else if keys.just_pressed(settings.key_settings) {
+        next_state.set(AppState::Settings);
+    }
+}
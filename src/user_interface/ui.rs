@@ -1,3 +1,4 @@
+use crate::staircase::{staircase_current_value, staircase_parameter_name};
 use crate::state::*;
 use crate::target;
 
@@ -72,17 +73,29 @@ pub fn update_ui_crosshair() {
     // UI прицел статичен в центре экрана
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_game_ui(
     mut text_query: Query<&mut Text, With<GameInfoText>>,
     test: Res<ReactionTest>,
     time: Res<Time>,
     mut hint_query: Query<&mut Visibility, With<StartCenterText>>,
     camera_query: Query<&Transform, With<PlayerCamera>>,
+    settings: Res<Settings>,
+    scenarios: Res<ScenarioLibrary>,
+    replay: Res<ReplayViewer>,
+    ghost: Res<GhostRace>,
 ) {
+    // Во время просмотра записи подсказка старта не нужна, результаты остаются на экране
+    if replay.active {
+        for mut v in hint_query.iter_mut() {
+            *v = Visibility::Hidden;
+        }
+        return;
+    }
     if let Ok(mut text) = text_query.single_mut() {
         if test.is_running {
             let current_time = time.elapsed_secs() - test.start_time;
-            let remaining = TEST_DURATION - current_time;
+            let remaining = test.scenario.test_duration - current_time;
 
             let cam_pos = camera_query.single().unwrap().translation;
 
@@ -90,66 +103,142 @@ pub fn update_game_ui(
             let crosshair_dir = test.crosshair_direction;
             let angular_error = target::calculate_angular_error(target_dir, crosshair_dir);
 
-            text.0 = format!(
-                "Time remaining: {:.1}s
+            if test.paused {
+                let paused_at = test.paused_at.saturating_sub(test.fixed_start);
+                text.0 = format!(
+                    "PAUSED at {:.1}s / {:.0}s\n\nSPACE - resume\nR - restart\nESC - settings (run is discarded)",
+                    paused_at.as_secs_f32(),
+                    test.scenario.test_duration
+                );
+            } else if current_time < 0.0 {
+                // Обратный отсчет: таргет уже виден, но стоит на месте
+                text.0 = format!("Starting in {:.0}...", (-current_time).ceil());
+            } else if test.scenario.mode.is_reaction() {
+                let completed = test
+                    .reaction_trials
+                    .iter()
+                    .filter(|t| !t.false_start)
+                    .count();
+                let shown = test
+                    .reaction_trial
+                    .as_ref()
+                    .is_some_and(|t| t.stimulus_shown && !t.auditory);
+                // В выборе и go/no-go подсказываем правило, а не ответ
+                let prompt = match (test.scenario.mode, shown) {
+                    (_, false) => "Wait...",
+                    (TestMode::Choice, true) => "Target colour - LEFT, other - RIGHT",
+                    (TestMode::GoNoGo, true) => "Click only the target colour",
+                    _ => "CLICK!",
+                };
+                text.0 = format!(
+                    "Time remaining: {:.1}s
+Trial: {}/{}
+False starts: {}
+Errors: {}
+{}",
+                    remaining,
+                    (completed + 1).min(test.scenario.reaction_trials as usize),
+                    test.scenario.reaction_trials,
+                    test.reaction_trials
+                        .iter()
+                        .filter(|t| t.false_start)
+                        .count(),
+                    test.reaction_trials
+                        .iter()
+                        .filter(|t| t.commission || t.omission)
+                        .count(),
+                    prompt
+                );
+            } else if test.scenario.mode == TestMode::Flick {
+                text.0 = format!(
+                    "Time remaining: {:.1}s
+Trial: {}/{}
+Hits: {}
+Misses: {}",
+                    remaining,
+                    (test.flick_trials.len() + 1).min(test.scenario.flick_trials as usize),
+                    test.scenario.flick_trials,
+                    test.flick_trials
+                        .iter()
+                        .filter(|t| t.time_to_hit.is_some())
+                        .count(),
+                    test.misses
+                );
+            } else {
+                text.0 = format!(
+                    "Time remaining: {:.1}s
 Accuracy: {:.2}
 Hits: {}
 Misses: {}
 Angular error: {:.1}°",
-                remaining,
-                test.hits as f32 / (test.hits + test.misses) as f32 * 100.0,
-                test.hits,
-                test.misses,
-                angular_error
-            );
+                    remaining,
+                    test.accuracy(),
+                    test.hits,
+                    test.misses,
+                    angular_error
+                );
+                if current_time < test.scenario.warmup {
+                    text.0.push_str("\nWarm-up (not scored)");
+                }
+                if ghost.racing {
+                    text.0
+                        .push_str(&format!("\nGhost: {:+.2} s on target", ghost.lead()));
+                }
+                if test.targets.len() > 1 {
+                    text.0.push_str(&format!(
+                        "\nTarget: {}/{}",
+                        test.active_target + 1,
+                        test.targets.len()
+                    ));
+                }
+                if let (Some(staircase), Some(value)) =
+                    (&test.scenario.staircase, staircase_current_value(&test))
+                {
+                    let (name, unit) = staircase_parameter_name(staircase.parameter);
+                    text.0
+                        .push_str(&format!("\nStaircase {}: {:.2} {}", name, value, unit));
+                }
+                if test.scenario.target_health > 0.0 {
+                    let health = test
+                        .targets
+                        .get(test.active_target)
+                        .map_or(test.scenario.target_health, |t| t.health);
+                    text.0.push_str(&format!(
+                        "\nKills: {}\nHealth: {:.0}/{:.0}",
+                        test.kills.len(),
+                        health,
+                        test.scenario.target_health
+                    ));
+                }
+            }
 
             // Принудительно скрываем подсказку при запуске теста
             for mut v in hint_query.iter_mut() {
                 *v = Visibility::Hidden;
             }
         } else if test.test_completed {
-            let rating = match test.average_delay {
-                d if d < 125.0 => "Supreme",
-                d if d < 135.0 => "Grandmaster",
-                d if d < 150.0 => "Master",
-                d if d < 165.0 => "Diamond",
-                d if d < 180.0 => "Platinum",
-                d if d < 200.0 => "Gold",
-                d if d < 220.0 => "Silver",
-                d if d < 250.0 => "Bronze",
-                _ => "Keep practicing",
-            };
-
-            // Results
-            text.0 = format!(
-                "TEST RESULTS
-Avg Reaction: {:.1} ms
-Count Dirs: {}
-React Dirs: {}
-Accuracy {:.2}%
-Hits: {}
-Miss: {}
-Avg error: {:.4}°
-Peak error: {:.4}°\n
-Rating: {}\n
-SPACE - new test\nESC - settings",
-                test.average_delay,
-                test.count_directions,
-                test.react_directions,
-                test.hits as f32 / (test.hits + test.misses) as f32 * 100.0,
-                test.hits,
-                test.misses,
-                test.rms_distance,
-                test.peak_angular_error,
-                rating
-            );
+            // Итоги показывает экран результатов
+            text.0.clear();
 
-            // Скрываем подсказку при завершении теста
             for mut v in hint_query.iter_mut() {
                 *v = Visibility::Hidden;
             }
         } else {
-            text.0 = "MODERN REACTION TEST\n".to_string();
+            let scenario = scenarios.get(&settings.scenario);
+            text.0 = format!(
+                "MODERN REACTION TEST\n\nScenario: {}\n{}",
+                scenario.name, scenario.description
+            );
+            text.0.push_str("\nH - history");
+            if ghost.is_active() {
+                text.0.push_str(&format!(
+                    "\nGhost: {} ({})\nG - clear ghost",
+                    ghost.title, ghost.scenario.name
+                ));
+            }
+            if settings.seed != 0 {
+                text.0.push_str(&format!("\nSeed: {}", settings.seed));
+            }
 
             for mut v in hint_query.iter_mut() {
                 *v = Visibility::Visible;
@@ -85,6 +85,28 @@ impl InputRowBuilder {
             _ => format!("{:.2}", value),
         };
 
+        self.spawn_text_input_row(
+            commands,
+            label,
+            formatted_value,
+            input_component,
+            buffer_component,
+        )
+    }
+
+    // Поле ввода с уже отформатированным значением (например, u64 seed)
+    pub fn spawn_text_input_row<T, B>(
+        &self,
+        commands: &mut Commands,
+        label: &str,
+        formatted_value: String,
+        input_component: T,
+        buffer_component: B,
+    ) -> Entity
+    where
+        T: Component,
+        B: Component,
+    {
         let row = commands
             .spawn((Node {
                 flex_direction: FlexDirection::Row,
@@ -291,6 +313,82 @@ impl InputRowBuilder {
 
         row
     }
+
+    // Кнопка, которая по клику переключает значение по кругу (например, сценарий)
+    pub fn spawn_selector_row<T, V>(
+        &self,
+        commands: &mut Commands,
+        label: &str,
+        value: &str,
+        selector_component: T,
+        value_component: V,
+    ) -> Entity
+    where
+        T: Component,
+        V: Component,
+    {
+        let row = commands
+            .spawn((Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(16.0),
+                align_items: AlignItems::Center,
+                ..default()
+            },))
+            .insert(ChildOf(self.parent))
+            .id();
+
+        // Label
+        commands
+            .spawn((
+                Text::new(label),
+                TextFont {
+                    font: self.asset_server.load(&self.font_file),
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(UI_COLORS.text_secondary),
+                Node {
+                    width: Val::Px(120.0),
+                    ..default()
+                },
+            ))
+            .insert(ChildOf(row));
+
+        // Selector button
+        let button = commands
+            .spawn((
+                Node {
+                    width: Val::Px(220.0),
+                    height: Val::Px(40.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(UI_COLORS.surface_light),
+                BorderRadius::all(Val::Px(8.0)),
+                Interaction::default(),
+                ModernButton,
+                selector_component,
+            ))
+            .insert(ChildOf(row))
+            .id();
+
+        commands
+            .spawn((
+                Text::new(format!("< {} >", value)),
+                TextFont {
+                    font: self.asset_server.load(&self.font_file),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(UI_COLORS.text_primary),
+                value_component,
+            ))
+            .insert(ChildOf(button));
+
+        row
+    }
 }
 
 // Исправить вспомогательные функции